@@ -0,0 +1,72 @@
+// 带副作用的设置 - 修改时立即生效，启动时以系统中的实际状态为准
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::gesture_service::{GESTURE_RECOGNITION_ENABLED, GESTURE_RECOGNITION_EVENT, GESTURE_RECOGNITION_SETTING};
+use crate::services::{is_auto_start_enabled, set_auto_start, DatabaseService};
+
+pub const ALWAYS_ON_TOP_SETTING: &str = "window_always_on_top";
+pub const AUTO_START_SETTING: &str = "auto_start";
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value.trim() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        other => Err(format!("{}只能为true或false，实际为{}", key, other)),
+    }
+}
+
+// 应用设置的副作用，普通设置直接返回；失败时调用方不应保存该设置
+pub fn apply_setting(app: &AppHandle, key: &str, value: &str) -> Result<(), String> {
+    match key {
+        ALWAYS_ON_TOP_SETTING => {
+            let always_on_top = parse_bool(key, value)?;
+            let window = app.get_webview_window("main").ok_or("主窗口不存在")?;
+            window.set_always_on_top(always_on_top).map_err(|e| e.to_string())
+        }
+        AUTO_START_SETTING => {
+            let enabled = parse_bool(key, value)?;
+            set_auto_start(app, enabled).map_err(|e| format!("设置开机启动失败: {}", e))
+        }
+        GESTURE_RECOGNITION_SETTING => {
+            let enabled = parse_bool(key, value)?;
+            GESTURE_RECOGNITION_ENABLED.store(enabled, Ordering::Relaxed);
+            let _ = app.emit(GESTURE_RECOGNITION_EVENT, enabled);
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+// 设置对应的实际状态，普通设置返回None
+pub fn current_state(app: &AppHandle, key: &str) -> Result<Option<bool>, String> {
+    match key {
+        ALWAYS_ON_TOP_SETTING => {
+            let window = app.get_webview_window("main").ok_or("主窗口不存在")?;
+            window.is_always_on_top().map(Some).map_err(|e| e.to_string())
+        }
+        AUTO_START_SETTING => is_auto_start_enabled(app)
+            .map(Some)
+            .map_err(|e| format!("读取开机启动状态失败: {}", e)),
+        GESTURE_RECOGNITION_SETTING => Ok(Some(GESTURE_RECOGNITION_ENABLED.load(Ordering::Relaxed))),
+        _ => Ok(None),
+    }
+}
+
+// 启动时将窗口置顶与开机启动的实际状态写入设置
+// 数据库每次启动都会重新写入默认值，不能据此修改系统状态（例如用户手动删除的启动项）
+pub async fn apply_startup_settings(app: &AppHandle, db: &DatabaseService) {
+    for key in [ALWAYS_ON_TOP_SETTING, AUTO_START_SETTING] {
+        let state = match current_state(app, key) {
+            Ok(Some(state)) => state,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("读取{}状态失败: {}", key, e);
+                continue;
+            }
+        };
+        if let Err(e) = db.set_setting(key, &state.to_string()).await {
+            eprintln!("同步设置{}失败: {}", key, e);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+// 剪贴板优化 - 读取剪贴板中的提示词，优化后写回剪贴板，支持撤销
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_notification::NotificationExt;
+
+pub const CLIPBOARD_OPTIMIZED_EVENT: &str = "clipboard://optimized";
+pub const CLIPBOARD_RESTORED_EVENT: &str = "clipboard://restored";
+
+// 可撤销的替换次数
+const MAX_UNDO: usize = 20;
+
+// 最近的剪贴板替换，用于撤销
+static CLIPBOARD_UNDO: Lazy<Mutex<VecDeque<ClipboardReplacement>>> = Lazy::new(|| {
+    Mutex::new(VecDeque::new())
+});
+
+// 一次剪贴板替换
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClipboardReplacement {
+    pub history_id: Option<i64>,
+    pub original: String,
+    pub optimized: String,
+    pub improvements: Vec<String>,
+}
+
+fn notify(app: &AppHandle, body: &str) {
+    let _ = app.notification()
+        .builder()
+        .title("CodingPal")
+        .body(body)
+        .show();
+}
+
+async fn replace_clipboard(app: &AppHandle) -> Result<ClipboardReplacement, String> {
+    let text = app.clipboard().read_text().map_err(|e| e.to_string())?;
+    if text.trim().is_empty() {
+        return Err("剪贴板中没有文本".to_string());
+    }
+
+    let result = crate::run_prompt_optimization(text).await?;
+    app.clipboard()
+        .write_text(result.optimized.clone())
+        .map_err(|e| format!("写入剪贴板失败: {}", e))?;
+
+    Ok(ClipboardReplacement {
+        history_id: result.history_id,
+        original: result.original,
+        optimized: result.optimized,
+        improvements: result.improvements,
+    })
+}
+
+// 优化剪贴板中的提示词并原地替换，结果通过系统通知告知用户
+// 快捷键和手势通常在IDE中触发，界面不可见，因此失败时同样发送通知
+pub async fn optimize_clipboard_in_place(app: &AppHandle) -> Result<ClipboardReplacement, String> {
+    match replace_clipboard(app).await {
+        Ok(replacement) => {
+            if let Ok(mut undo) = CLIPBOARD_UNDO.lock() {
+                undo.push_back(replacement.clone());
+                while undo.len() > MAX_UNDO {
+                    undo.pop_front();
+                }
+            }
+            let _ = app.emit(CLIPBOARD_OPTIMIZED_EVENT, &replacement);
+            notify(app, "提示词已优化并写回剪贴板，可直接粘贴");
+            Ok(replacement)
+        }
+        Err(e) => {
+            notify(app, &format!("优化剪贴板失败: {}", e));
+            Err(e)
+        }
+    }
+}
+
+// Tauri命令：优化剪贴板中的提示词并写回剪贴板
+#[tauri::command]
+pub async fn optimize_clipboard(app: AppHandle) -> Result<ClipboardReplacement, String> {
+    optimize_clipboard_in_place(&app).await
+}
+
+// Tauri命令：撤销剪贴板优化，将原始提示词写回剪贴板
+// 指定history_id时从优化历史中恢复，否则撤销最近一次替换
+#[tauri::command]
+pub async fn undo_clipboard_optimization(app: AppHandle, history_id: Option<i64>) -> Result<String, String> {
+    let original = match history_id {
+        Some(id) => {
+            let db = {
+                let db_guard = crate::DATABASE.lock().map_err(|e| e.to_string())?;
+                db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+            };
+            let record = db.get_optimization_record(id)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("优化记录不存在")?;
+            if let Ok(mut undo) = CLIPBOARD_UNDO.lock() {
+                undo.retain(|replacement| replacement.history_id != Some(id));
+            }
+            record.original_prompt
+        }
+        None => {
+            let mut undo = CLIPBOARD_UNDO.lock().map_err(|e| e.to_string())?;
+            undo.pop_back().ok_or("没有可撤销的剪贴板优化")?.original
+        }
+    };
+
+    app.clipboard()
+        .write_text(original.clone())
+        .map_err(|e| format!("写入剪贴板失败: {}", e))?;
+    let _ = app.emit(CLIPBOARD_RESTORED_EVENT, &original);
+    notify(&app, "已恢复优化前的提示词");
+
+    Ok(original)
+}
@@ -0,0 +1,520 @@
+// 手势动作绑定 - 将识别到的手势映射为后端动作
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::app_settings::ALWAYS_ON_TOP_SETTING;
+use crate::clipboard_optimizer::optimize_clipboard_in_place;
+use crate::services::{TaskChange, TASK_CHANGED_EVENT, TASK_STATUS_ACTIVE, TASK_STATUS_DONE};
+
+pub const GESTURE_ACTION_EVENT: &str = "gesture://action";
+pub const MONITORING_PAUSED_EVENT: &str = "monitoring://paused";
+pub const ACTIVE_TASK_CHANGED_EVENT: &str = "task://active_changed";
+pub const HISTORY_SCROLL_EVENT: &str = "history://scroll";
+
+// 每个绑定上次触发的时间，用于冷却判断
+static BINDING_COOLDOWNS: Lazy<Mutex<HashMap<i64, Instant>>> = Lazy::new(|| {
+    Mutex::new(HashMap::new())
+});
+
+// 可绑定的后端动作
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GestureAction {
+    OptimizeClipboard,
+    ToggleAlwaysOnTop,
+    MarkActiveTaskDone,
+    ToggleMonitoring,
+    RunShellCommand,
+    NextTask,
+    PreviousTask,
+    ScrollHistory,
+}
+
+impl GestureAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GestureAction::OptimizeClipboard => "optimize_clipboard",
+            GestureAction::ToggleAlwaysOnTop => "toggle_always_on_top",
+            GestureAction::MarkActiveTaskDone => "mark_active_task_done",
+            GestureAction::ToggleMonitoring => "toggle_monitoring",
+            GestureAction::RunShellCommand => "run_shell_command",
+            GestureAction::NextTask => "next_task",
+            GestureAction::PreviousTask => "previous_task",
+            GestureAction::ScrollHistory => "scroll_history",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "optimize_clipboard" => Some(GestureAction::OptimizeClipboard),
+            "toggle_always_on_top" => Some(GestureAction::ToggleAlwaysOnTop),
+            "mark_active_task_done" => Some(GestureAction::MarkActiveTaskDone),
+            "toggle_monitoring" => Some(GestureAction::ToggleMonitoring),
+            "run_shell_command" => Some(GestureAction::RunShellCommand),
+            "next_task" => Some(GestureAction::NextTask),
+            "previous_task" => Some(GestureAction::PreviousTask),
+            "scroll_history" => Some(GestureAction::ScrollHistory),
+            _ => None,
+        }
+    }
+}
+
+// 手势绑定结构
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestureBinding {
+    pub id: Option<i64>,
+    pub gesture_type: String,
+    pub action: GestureAction,
+    pub action_args: Option<String>, // 动作参数，如自定义shell命令、历史滚动方向（up/down）
+    pub cooldown_ms: i64,
+    pub enabled: bool,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+// 动作触发日志结构
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestureActionLog {
+    pub id: Option<i64>,
+    pub binding_id: Option<i64>, // 绑定删除后为空，日志保留
+    pub gesture_type: String,
+    pub action: String,
+    pub confidence: f64,
+    pub record_id: Option<i64>,
+    pub success: bool,
+    pub message: Option<String>,
+    pub fired_at: Option<String>,
+}
+
+// 初始化手势动作相关表
+pub async fn init_gesture_action_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    // 创建手势绑定表
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS gesture_bindings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            gesture_type VARCHAR(20) NOT NULL,
+            action VARCHAR(50) NOT NULL,
+            action_args TEXT,
+            cooldown_ms INTEGER DEFAULT 2000,
+            enabled BOOLEAN DEFAULT true,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_gesture_bindings_type ON gesture_bindings(gesture_type)")
+        .execute(pool)
+        .await?;
+
+    // 创建动作日志表
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS gesture_action_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            binding_id INTEGER,
+            gesture_type VARCHAR(20) NOT NULL,
+            action VARCHAR(50) NOT NULL,
+            confidence REAL NOT NULL,
+            record_id INTEGER,
+            success BOOLEAN NOT NULL,
+            message TEXT,
+            fired_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (binding_id) REFERENCES gesture_bindings(id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_gesture_action_logs_time ON gesture_action_logs(fired_at DESC)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 检查手势是否满足配置阈值，并触发所有未处于冷却期的绑定
+pub async fn fire_gesture_bindings(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    gesture_type: &str,
+    confidence: f64,
+    record_id: Option<i64>,
+) -> Result<(), sqlx::Error> {
+    let config = sqlx::query("SELECT threshold, enabled FROM gesture_configs WHERE gesture_type = ?")
+        .bind(gesture_type)
+        .fetch_optional(pool)
+        .await?;
+
+    let Some(config) = config else { return Ok(()) };
+    let threshold: f64 = config.get("threshold");
+    let enabled: bool = config.get("enabled");
+    if !enabled || confidence < threshold {
+        return Ok(());
+    }
+
+    let bindings = sqlx::query_as::<_, GestureBinding>(
+        "SELECT * FROM gesture_bindings WHERE gesture_type = ? AND enabled = true ORDER BY id"
+    )
+    .bind(gesture_type)
+    .fetch_all(pool)
+    .await?;
+
+    for binding in bindings {
+        let Some(binding_id) = binding.id else { continue };
+        if !try_start_cooldown(binding_id, binding.cooldown_ms) {
+            continue;
+        }
+
+        let app = app.clone();
+        let pool = pool.clone();
+        tauri::async_runtime::spawn(async move {
+            let outcome = execute_action(&app, binding.action, binding.action_args.as_deref()).await;
+            let (success, message) = match outcome {
+                Ok(message) => (true, message),
+                Err(message) => (false, Some(message)),
+            };
+
+            let mut log = GestureActionLog {
+                id: None,
+                binding_id: Some(binding_id),
+                gesture_type: binding.gesture_type.clone(),
+                action: binding.action.as_str().to_string(),
+                confidence,
+                record_id,
+                success,
+                message,
+                fired_at: None,
+            };
+
+            match save_action_log(&pool, &log).await {
+                Ok(id) => log.id = Some(id),
+                Err(e) => eprintln!("保存手势动作日志失败: {}", e),
+            }
+            let _ = app.emit(GESTURE_ACTION_EVENT, &log);
+        });
+    }
+
+    Ok(())
+}
+
+fn try_start_cooldown(binding_id: i64, cooldown_ms: i64) -> bool {
+    let Ok(mut cooldowns) = BINDING_COOLDOWNS.lock() else { return false };
+    let now = Instant::now();
+    let cooldown = Duration::from_millis(cooldown_ms.max(0) as u64);
+
+    if let Some(last) = cooldowns.get(&binding_id) {
+        if now.duration_since(*last) < cooldown {
+            return false;
+        }
+    }
+
+    cooldowns.insert(binding_id, now);
+    true
+}
+
+async fn save_action_log(pool: &SqlitePool, log: &GestureActionLog) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO gesture_action_logs (binding_id, gesture_type, action, confidence, record_id, success, message) VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(log.binding_id)
+    .bind(&log.gesture_type)
+    .bind(&log.action)
+    .bind(log.confidence)
+    .bind(log.record_id)
+    .bind(log.success)
+    .bind(&log.message)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+// 执行动作，成功时返回可选的说明信息
+pub(crate) async fn execute_action(app: &AppHandle, action: GestureAction, args: Option<&str>) -> Result<Option<String>, String> {
+    match action {
+        GestureAction::OptimizeClipboard => {
+            let replacement = optimize_clipboard_in_place(app).await?;
+            Ok(replacement.history_id.map(|id| id.to_string()))
+        }
+        GestureAction::ToggleAlwaysOnTop => {
+            let window = app.get_webview_window("main").ok_or("主窗口不存在")?;
+            let always_on_top = !window.is_always_on_top().map_err(|e| e.to_string())?;
+            window.set_always_on_top(always_on_top).map_err(|e| e.to_string())?;
+
+            let db = {
+                let db_guard = crate::DATABASE.lock().map_err(|e| e.to_string())?;
+                db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+            };
+            db.set_setting(ALWAYS_ON_TOP_SETTING, &always_on_top.to_string())
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(Some(always_on_top.to_string()))
+        }
+        GestureAction::MarkActiveTaskDone => {
+            let db = {
+                let db_guard = crate::DATABASE.lock().map_err(|e| e.to_string())?;
+                db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+            };
+            let task_id = db.get_active_task_id()
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("没有激活的任务")?;
+            db.update_task_status(task_id, TASK_STATUS_DONE)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let change = TaskChange::StatusChanged { task_id, status: TASK_STATUS_DONE.to_string() };
+            let _ = app.emit(TASK_CHANGED_EVENT, &change);
+            Ok(Some(task_id.to_string()))
+        }
+        GestureAction::ToggleMonitoring => {
+            let paused = {
+                let mut monitor = crate::PROCESS_MONITOR.lock().map_err(|e| e.to_string())?;
+                let paused = !monitor.is_paused();
+                monitor.set_paused(paused);
+                paused
+            };
+            let _ = app.emit(MONITORING_PAUSED_EVENT, paused);
+            Ok(Some(paused.to_string()))
+        }
+        GestureAction::RunShellCommand => {
+            let command = args.filter(|c| !c.trim().is_empty()).ok_or("未配置shell命令")?;
+            run_shell_command(command).await
+        }
+        GestureAction::NextTask => switch_active_task(app, 1).await,
+        GestureAction::PreviousTask => switch_active_task(app, -1).await,
+        GestureAction::ScrollHistory => {
+            let direction = match args.map(str::trim) {
+                Some("up") => "up",
+                None | Some("") | Some("down") => "down",
+                Some(other) => return Err(format!("未知的滚动方向: {}", other)),
+            };
+            let _ = app.emit(HISTORY_SCROLL_EVENT, direction);
+            Ok(Some(direction.to_string()))
+        }
+    }
+}
+
+// 在进行中的任务之间循环切换当前任务
+async fn switch_active_task(app: &AppHandle, step: i64) -> Result<Option<String>, String> {
+    let db = {
+        let db_guard = crate::DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let tasks: Vec<_> = db.get_task_folders()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|task| task.status == TASK_STATUS_ACTIVE)
+        .collect();
+    if tasks.is_empty() {
+        return Err("没有进行中的任务".to_string());
+    }
+
+    let active_id = db.get_active_task_id().await.map_err(|e| e.to_string())?;
+    let next = match tasks.iter().position(|task| Some(task.id) == active_id) {
+        Some(index) => (index as i64 + step).rem_euclid(tasks.len() as i64) as usize,
+        None if step > 0 => 0,
+        None => tasks.len() - 1,
+    };
+    let task = &tasks[next];
+
+    db.set_setting("active_task_id", &task.id.to_string())
+        .await
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit(ACTIVE_TASK_CHANGED_EVENT, task);
+    Ok(Some(task.id.to_string()))
+}
+
+async fn run_shell_command(command: &str) -> Result<Option<String>, String> {
+    #[cfg(windows)]
+    let output = tokio::process::Command::new("cmd").args(["/C", command]).output().await;
+    #[cfg(not(windows))]
+    let output = tokio::process::Command::new("sh").args(["-c", command]).output().await;
+
+    let output = output.map_err(|e| format!("执行命令失败: {}", e))?;
+    if output.status.success() {
+        Ok(Some(format!("exit {}", output.status.code().unwrap_or(0))))
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("命令退出码 {:?}: {}", output.status.code(), stderr.trim()))
+    }
+}
+
+// Tauri命令：获取手势绑定
+#[tauri::command]
+pub async fn get_gesture_bindings() -> Result<Vec<GestureBinding>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let bindings = sqlx::query_as::<_, GestureBinding>("SELECT * FROM gesture_bindings ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(bindings)
+}
+
+// Tauri命令：新增或更新手势绑定，返回绑定ID
+#[tauri::command]
+pub async fn save_gesture_binding(binding: GestureBinding) -> Result<i64, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+
+    if binding.action == GestureAction::RunShellCommand
+        && binding.action_args.as_deref().map_or(true, |c| c.trim().is_empty())
+    {
+        return Err("shell命令不能为空".to_string());
+    }
+
+    match binding.id {
+        Some(id) => {
+            sqlx::query(
+                "UPDATE gesture_bindings SET gesture_type = ?, action = ?, action_args = ?, cooldown_ms = ?, enabled = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
+            )
+            .bind(&binding.gesture_type)
+            .bind(binding.action.as_str())
+            .bind(&binding.action_args)
+            .bind(binding.cooldown_ms)
+            .bind(binding.enabled)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+            Ok(id)
+        }
+        None => {
+            let result = sqlx::query(
+                "INSERT INTO gesture_bindings (gesture_type, action, action_args, cooldown_ms, enabled) VALUES (?, ?, ?, ?, ?)"
+            )
+            .bind(&binding.gesture_type)
+            .bind(binding.action.as_str())
+            .bind(&binding.action_args)
+            .bind(binding.cooldown_ms)
+            .bind(binding.enabled)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+            Ok(result.last_insert_rowid())
+        }
+    }
+}
+
+// Tauri命令：删除手势绑定
+#[tauri::command]
+pub async fn delete_gesture_binding(id: i64) -> Result<bool, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let mut tx = db.get_pool().begin().await.map_err(|e| e.to_string())?;
+
+    // 触发日志保留，只解除与绑定的关联
+    sqlx::query("UPDATE gesture_action_logs SET binding_id = NULL WHERE binding_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let result = sqlx::query("DELETE FROM gesture_bindings WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    if let Ok(mut cooldowns) = BINDING_COOLDOWNS.lock() {
+        cooldowns.remove(&id);
+    }
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Tauri命令：获取动作触发日志
+#[tauri::command]
+pub async fn get_gesture_action_logs(limit: Option<i32>) -> Result<Vec<GestureActionLog>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let logs = sqlx::query_as::<_, GestureActionLog>(
+        "SELECT * FROM gesture_action_logs ORDER BY fired_at DESC, id DESC LIMIT ?"
+    )
+    .bind(limit.unwrap_or(50))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(logs)
+}
+
+// Tauri命令：暂停或恢复IDE进程监控
+#[tauri::command]
+pub async fn set_monitoring_paused(app: AppHandle, paused: bool) -> Result<(), String> {
+    {
+        let mut monitor = crate::PROCESS_MONITOR.lock().map_err(|e| e.to_string())?;
+        monitor.set_paused(paused);
+    }
+    let _ = app.emit(MONITORING_PAUSED_EVENT, paused);
+    Ok(())
+}
+
+// 实现 sqlx::FromRow for GestureBinding
+impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for GestureBinding {
+    fn from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        let action: String = row.get("action");
+        Ok(GestureBinding {
+            id: Some(row.get("id")),
+            gesture_type: row.get("gesture_type"),
+            action: GestureAction::parse(&action).ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: "action".to_string(),
+                source: format!("未知的手势动作: {}", action).into(),
+            })?,
+            action_args: row.get("action_args"),
+            cooldown_ms: row.get("cooldown_ms"),
+            enabled: row.get("enabled"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        })
+    }
+}
+
+// 实现 sqlx::FromRow for GestureActionLog
+impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for GestureActionLog {
+    fn from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(GestureActionLog {
+            id: Some(row.get("id")),
+            binding_id: row.get("binding_id"),
+            gesture_type: row.get("gesture_type"),
+            action: row.get("action"),
+            confidence: row.get("confidence"),
+            record_id: row.get("record_id"),
+            success: row.get("success"),
+            message: row.get("message"),
+            fired_at: row.get("fired_at"),
+        })
+    }
+}
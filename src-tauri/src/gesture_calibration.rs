@@ -0,0 +1,384 @@
+// 手势阈值校准 - 用户逐个做出手势，根据置信度分布为每个手势推荐阈值
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::gesture_keypoints::decode_landmarks;
+use crate::gesture_motion::GESTURE_KIND_STATIC;
+use crate::gesture_service::{classify_hand, GestureClassification, Landmark};
+use crate::gesture_smoothing::DEFAULT_HYSTERESIS;
+
+// 推荐阈值的范围，低于下限时分类器本身已判定为unknown
+// 开始手势需高于 threshold + hysteresis，上限留出滞回余量，否则置信度无法达到
+const MIN_THRESHOLD: f64 = 0.5;
+const MAX_THRESHOLD: f64 = 1.0 - DEFAULT_HYSTERESIS;
+// 每个手势至少需要的样本数，不足时保留当前阈值
+const MIN_SAMPLES_PER_GESTURE: usize = 3;
+// 表示"没有做任何手势"的样本标签，用于统计误触发
+pub const NO_GESTURE_LABEL: &str = "unknown";
+
+// 当前进行中的校准会话
+static CALIBRATION: Lazy<Mutex<Option<CalibrationSession>>> = Lazy::new(|| {
+    Mutex::new(None)
+});
+
+#[derive(Debug, Clone)]
+struct CalibrationSample {
+    target: String,
+    scores: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CalibrationSession {
+    samples: Vec<CalibrationSample>,
+}
+
+// 校准进度：每个目标手势已采集的样本数
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalibrationStatus {
+    pub active: bool,
+    pub sample_counts: BTreeMap<String, usize>,
+}
+
+// 单个手势的阈值建议
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThresholdProposal {
+    pub gesture_type: String,
+    pub samples: usize,
+    pub current_threshold: f64,
+    pub proposed_threshold: f64,
+    // 该手势的样本被正确识别的比例
+    pub current_recall: f64,
+    pub proposed_recall: f64,
+    // 其他样本被误识别为该手势的次数
+    pub current_false_accepts: usize,
+    pub proposed_false_accepts: usize,
+}
+
+// 校准结果：各手势建议及整体预期准确率
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalibrationProposal {
+    pub total_samples: usize,
+    pub current_accuracy: f64,
+    pub proposed_accuracy: f64,
+    pub gestures: Vec<ThresholdProposal>,
+}
+
+impl CalibrationSession {
+    fn status(&self) -> CalibrationStatus {
+        let mut sample_counts = BTreeMap::new();
+        for sample in &self.samples {
+            *sample_counts.entry(sample.target.clone()).or_insert(0) += 1;
+        }
+        CalibrationStatus { active: true, sample_counts }
+    }
+}
+
+// 按给定阈值预测样本的手势：取最高分，低于其阈值时为unknown
+fn predict<'a>(scores: &'a BTreeMap<String, f64>, thresholds: &HashMap<String, f64>) -> Option<&'a str> {
+    let (gesture, score) = scores.iter().max_by(|a, b| a.1.total_cmp(b.1))?;
+    let threshold = thresholds.get(gesture)?;
+    (*score >= *threshold).then_some(gesture.as_str())
+}
+
+fn is_correct(sample: &CalibrationSample, thresholds: &HashMap<String, f64>) -> bool {
+    match predict(&sample.scores, thresholds) {
+        Some(gesture) => gesture == sample.target,
+        None => sample.target == NO_GESTURE_LABEL,
+    }
+}
+
+fn accuracy(samples: &[CalibrationSample], thresholds: &HashMap<String, f64>) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.iter().filter(|sample| is_correct(sample, thresholds)).count() as f64 / samples.len() as f64
+}
+
+// 统计某个手势的召回率与误识别次数
+fn gesture_metrics(samples: &[CalibrationSample], gesture: &str, thresholds: &HashMap<String, f64>) -> (f64, usize) {
+    let mut positives = 0;
+    let mut recalled = 0;
+    let mut false_accepts = 0;
+
+    for sample in samples {
+        let predicted = predict(&sample.scores, thresholds) == Some(gesture);
+        if sample.target == gesture {
+            positives += 1;
+            if predicted {
+                recalled += 1;
+            }
+        } else if predicted {
+            false_accepts += 1;
+        }
+    }
+
+    let recall = if positives > 0 { recalled as f64 / positives as f64 } else { 0.0 };
+    (recall, false_accepts)
+}
+
+// 为单个手势选择阈值
+// 阈值只影响最高分为该手势的样本：目标相同的低于阈值会被漏识别，目标不同的高于阈值会被误识别。
+// 在相邻样本分数的中点中选择漏识别+误识别最少的一个，相同时取间隔最大的，使阈值离两侧样本都尽量远
+fn choose_threshold(samples: &[CalibrationSample], gesture: &str) -> Option<f64> {
+    let mut relevant: Vec<(f64, bool)> = samples
+        .iter()
+        .filter_map(|sample| {
+            let (top, score) = sample.scores.iter().max_by(|a, b| a.1.total_cmp(b.1))?;
+            (top == gesture).then_some((*score, sample.target == gesture))
+        })
+        .collect();
+    if relevant.iter().filter(|(_, genuine)| *genuine).count() < MIN_SAMPLES_PER_GESTURE {
+        return None;
+    }
+    relevant.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut points: Vec<f64> = vec![MIN_THRESHOLD];
+    points.extend(relevant.iter().map(|(score, _)| *score).filter(|s| *s > MIN_THRESHOLD && *s < MAX_THRESHOLD));
+    points.push(MAX_THRESHOLD);
+    points.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+
+    let errors_at = |threshold: f64| {
+        relevant
+            .iter()
+            .filter(|(score, genuine)| if *genuine { *score < threshold } else { *score >= threshold })
+            .count()
+    };
+
+    points
+        .windows(2)
+        .map(|pair| ((pair[0] + pair[1]) / 2.0, pair[1] - pair[0]))
+        .min_by(|(a, gap_a), (b, gap_b)| errors_at(*a).cmp(&errors_at(*b)).then(gap_b.total_cmp(gap_a)))
+        .map(|(threshold, _)| (threshold * 100.0).round() / 100.0)
+}
+
+async fn load_static_thresholds(pool: &SqlitePool) -> Result<HashMap<String, f64>, sqlx::Error> {
+    let rows = sqlx::query("SELECT gesture_type, threshold FROM gesture_configs WHERE kind = ?")
+        .bind(GESTURE_KIND_STATIC)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("gesture_type"), row.get("threshold")))
+        .collect())
+}
+
+fn propose(samples: &[CalibrationSample], current: &HashMap<String, f64>) -> CalibrationProposal {
+    let mut proposed = current.clone();
+    for (gesture, threshold) in proposed.iter_mut() {
+        if let Some(choice) = choose_threshold(samples, gesture) {
+            *threshold = choice;
+        }
+    }
+
+    let mut gestures: Vec<ThresholdProposal> = current
+        .iter()
+        .filter_map(|(gesture, current_threshold)| {
+            let count = samples.iter().filter(|sample| &sample.target == gesture).count();
+            if count == 0 {
+                return None;
+            }
+            let (current_recall, current_false_accepts) = gesture_metrics(samples, gesture, current);
+            let (proposed_recall, proposed_false_accepts) = gesture_metrics(samples, gesture, &proposed);
+            Some(ThresholdProposal {
+                gesture_type: gesture.clone(),
+                samples: count,
+                current_threshold: *current_threshold,
+                proposed_threshold: proposed[gesture],
+                current_recall,
+                proposed_recall,
+                current_false_accepts,
+                proposed_false_accepts,
+            })
+        })
+        .collect();
+    gestures.sort_by(|a, b| a.gesture_type.cmp(&b.gesture_type));
+
+    CalibrationProposal {
+        total_samples: samples.len(),
+        current_accuracy: accuracy(samples, current),
+        proposed_accuracy: accuracy(samples, &proposed),
+        gestures,
+    }
+}
+
+// 用户纠正过且保存了关键点的记录，以纠正后的手势作为样本标签
+async fn load_corrected_samples(pool: &SqlitePool) -> Result<Vec<CalibrationSample>, String> {
+    let rows = sqlx::query(
+        r#"
+        SELECT r.corrected_type, k.keypoints
+        FROM gesture_records r
+        JOIN keypoint_data k ON k.record_id = r.id
+        WHERE r.corrected_type IS NOT NULL
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let keypoints: Vec<u8> = row.get("keypoints");
+            let landmarks = decode_landmarks(&keypoints).ok()?;
+            let classification = classify_hand(&landmarks).ok()?;
+            Some(CalibrationSample {
+                target: row.get("corrected_type"),
+                scores: classification.scores,
+            })
+        })
+        .collect())
+}
+
+// Tauri命令：开始校准，丢弃之前未完成的样本
+// include_corrections为true时，用户纠正过的历史记录也作为样本
+#[tauri::command]
+pub async fn start_gesture_calibration(include_corrections: Option<bool>) -> Result<CalibrationStatus, String> {
+    let mut session = CalibrationSession::default();
+    if include_corrections.unwrap_or(false) {
+        use crate::DATABASE;
+        let db = {
+            let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+            db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+        };
+        session.samples = load_corrected_samples(db.get_pool()).await?;
+    }
+
+    let status = session.status();
+    *CALIBRATION.lock().map_err(|e| e.to_string())? = Some(session);
+    Ok(status)
+}
+
+// Tauri命令：获取校准进度
+#[tauri::command]
+pub async fn get_gesture_calibration_status() -> Result<CalibrationStatus, String> {
+    let calibration = CALIBRATION.lock().map_err(|e| e.to_string())?;
+    Ok(match calibration.as_ref() {
+        Some(session) => session.status(),
+        None => CalibrationStatus { active: false, sample_counts: BTreeMap::new() },
+    })
+}
+
+// Tauri命令：提交一帧校准样本，target为用户正在做的手势（没有做手势时为unknown）
+#[tauri::command]
+pub async fn add_gesture_calibration_sample(
+    target: String,
+    keypoints: Vec<Landmark>,
+) -> Result<GestureClassification, String> {
+    let classification = classify_hand(&keypoints)?;
+
+    let mut calibration = CALIBRATION.lock().map_err(|e| e.to_string())?;
+    let session = calibration.as_mut().ok_or("校准尚未开始")?;
+    session.samples.push(CalibrationSample {
+        target,
+        scores: classification.scores.clone(),
+    });
+
+    Ok(classification)
+}
+
+// Tauri命令：根据已采集样本计算阈值建议，不修改配置
+#[tauri::command]
+pub async fn propose_gesture_thresholds() -> Result<CalibrationProposal, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let current = load_static_thresholds(db.get_pool()).await.map_err(|e| e.to_string())?;
+
+    let samples = {
+        let calibration = CALIBRATION.lock().map_err(|e| e.to_string())?;
+        calibration.as_ref().ok_or("校准尚未开始")?.samples.clone()
+    };
+    if samples.is_empty() {
+        return Err("还没有采集校准样本".to_string());
+    }
+
+    Ok(propose(&samples, &current))
+}
+
+// Tauri命令：应用确认后的阈值并结束校准，返回更新的手势数
+#[tauri::command]
+pub async fn apply_gesture_thresholds(thresholds: HashMap<String, f64>) -> Result<usize, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+
+    if let Some((gesture, _)) = thresholds.iter().find(|(_, t)| !(0.0..=MAX_THRESHOLD).contains(*t)) {
+        return Err(format!("{}的阈值需在0~{}之间", gesture, MAX_THRESHOLD));
+    }
+
+    // 全部阈值在同一事务中更新，避免只应用一部分
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut updated = 0;
+    for (gesture_type, threshold) in &thresholds {
+        let result = sqlx::query(
+            "UPDATE gesture_configs SET threshold = ?, updated_at = CURRENT_TIMESTAMP WHERE gesture_type = ?"
+        )
+        .bind(threshold)
+        .bind(gesture_type)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        updated += result.rows_affected() as usize;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    if let Ok(mut calibration) = CALIBRATION.lock() {
+        *calibration = None;
+    }
+
+    Ok(updated)
+}
+
+// Tauri命令：放弃校准
+#[tauri::command]
+pub async fn cancel_gesture_calibration() -> Result<(), String> {
+    let mut calibration = CALIBRATION.lock().map_err(|e| e.to_string())?;
+    *calibration = None;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(target: &str, gesture: &str, score: f64) -> CalibrationSample {
+        CalibrationSample {
+            target: target.to_string(),
+            scores: BTreeMap::from([(gesture.to_string(), score)]),
+        }
+    }
+
+    #[test]
+    fn proposal_leaves_room_for_hysteresis() {
+        // 置信度都接近1时，推荐值也不超过上限，仍能达到开始所需的 threshold + hysteresis
+        let mut samples: Vec<_> = (0..5).map(|_| sample("fist", "fist", 0.97)).collect();
+        samples.push(sample(NO_GESTURE_LABEL, "fist", 0.99));
+
+        let threshold = choose_threshold(&samples, "fist").unwrap();
+        assert!(threshold + DEFAULT_HYSTERESIS <= 1.0 + 1e-9);
+    }
+
+    #[test]
+    fn proposal_separates_genuine_and_false_samples() {
+        let mut samples: Vec<_> = [0.8, 0.85, 0.9].iter().map(|s| sample("fist", "fist", *s)).collect();
+        samples.extend([0.55, 0.6].iter().map(|s| sample(NO_GESTURE_LABEL, "fist", *s)));
+
+        let threshold = choose_threshold(&samples, "fist").unwrap();
+        assert!(threshold > 0.6 && threshold <= 0.8);
+    }
+
+    #[test]
+    fn too_few_samples_keep_current_threshold() {
+        let samples = vec![sample("fist", "fist", 0.9), sample("fist", "fist", 0.9)];
+        assert_eq!(choose_threshold(&samples, "fist"), None);
+    }
+}
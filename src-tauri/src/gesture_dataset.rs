@@ -0,0 +1,261 @@
+// 手势数据集导出 - 将带标签的手势记录及关键点导出为训练数据
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::gesture_keypoints::decode_landmarks;
+use crate::gesture_service::{normalize_sql_timestamp, HAND_LANDMARK_COUNT};
+
+pub const DEFAULT_DATASET_DIR: &str = "CodingPal/datasets";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DatasetFormat {
+    Jsonl,
+    Csv,
+}
+
+impl DatasetFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            DatasetFormat::Jsonl => "jsonl",
+            DatasetFormat::Csv => "csv",
+        }
+    }
+}
+
+// 导出选项，所有过滤条件均可选
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DatasetExportOptions {
+    pub format: DatasetFormat,
+    pub output_path: Option<String>, // 为空时写入dataset_dir设置的目录
+    pub gesture_types: Option<Vec<String>>,
+    pub min_confidence: Option<f64>,
+    pub max_confidence: Option<f64>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    #[serde(default)]
+    pub relabel: HashMap<i64, String>, // 记录ID -> 导出时使用的标签
+    #[serde(default)]
+    pub exclude_record_ids: Vec<i64>,
+}
+
+// 导出结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DatasetExportResult {
+    pub path: String,
+    pub rows: usize,
+    pub records: usize,
+    pub relabeled: usize,
+    pub corrected: usize, // 使用用户纠正标签的记录
+    pub skipped: usize, // 关键点数据无法解码或数量不对的行
+}
+
+// 数据集中的一行：一组关键点及其标签
+#[derive(Debug, Serialize)]
+struct DatasetRow<'a> {
+    record_id: i64,
+    label: &'a str,
+    confidence: f64,
+    detected_at: &'a str,
+    frame_width: i32,
+    frame_height: i32,
+    landmarks: Vec<[f64; 3]>,
+}
+
+enum DatasetWriter {
+    Jsonl(BufWriter<File>),
+    Csv(Box<csv::Writer<File>>),
+}
+
+impl DatasetWriter {
+    fn create(format: DatasetFormat, path: &Path) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("无法创建导出文件: {}", e))?;
+        match format {
+            DatasetFormat::Jsonl => Ok(DatasetWriter::Jsonl(BufWriter::new(file))),
+            DatasetFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(file);
+                let mut header = vec![
+                    "record_id".to_string(),
+                    "label".to_string(),
+                    "confidence".to_string(),
+                    "detected_at".to_string(),
+                    "frame_width".to_string(),
+                    "frame_height".to_string(),
+                ];
+                for i in 0..HAND_LANDMARK_COUNT {
+                    header.extend([format!("x{}", i), format!("y{}", i), format!("z{}", i)]);
+                }
+                writer.write_record(&header).map_err(|e| e.to_string())?;
+                Ok(DatasetWriter::Csv(Box::new(writer)))
+            }
+        }
+    }
+
+    fn write(&mut self, row: &DatasetRow) -> Result<(), String> {
+        match self {
+            DatasetWriter::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, row).map_err(|e| e.to_string())?;
+                writer.write_all(b"\n").map_err(|e| e.to_string())
+            }
+            DatasetWriter::Csv(writer) => {
+                let mut fields = vec![
+                    row.record_id.to_string(),
+                    row.label.to_string(),
+                    row.confidence.to_string(),
+                    row.detected_at.to_string(),
+                    row.frame_width.to_string(),
+                    row.frame_height.to_string(),
+                ];
+                for point in &row.landmarks {
+                    fields.extend(point.iter().map(|v| v.to_string()));
+                }
+                writer.write_record(&fields).map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            DatasetWriter::Jsonl(mut writer) => writer.flush().map_err(|e| e.to_string()),
+            DatasetWriter::Csv(mut writer) => writer.flush().map_err(|e| e.to_string()),
+        }
+    }
+}
+
+async fn resolve_output_path(db: &crate::services::DatabaseService, options: &DatasetExportOptions) -> Result<PathBuf, String> {
+    if let Some(path) = options.output_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+
+    let dir = db.get_setting("dataset_dir")
+        .await
+        .map_err(|e| e.to_string())?
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_DATASET_DIR.to_string());
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    Ok(PathBuf::from(dir).join(format!(
+        "gesture-dataset-{}.{}",
+        Utc::now().format("%Y%m%d-%H%M%S"),
+        options.format.extension()
+    )))
+}
+
+// Tauri命令：导出手势数据集，每组关键点一行
+#[tauri::command]
+pub async fn export_gesture_dataset(options: DatasetExportOptions) -> Result<DatasetExportResult, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+
+    let path = resolve_output_path(&db, &options).await?;
+    let start = options.start.as_deref().map(normalize_sql_timestamp);
+    let end = options.end.as_deref().map(normalize_sql_timestamp);
+    let gesture_types: Option<HashSet<&str>> = options.gesture_types
+        .as_ref()
+        .map(|types| types.iter().map(String::as_str).collect());
+    let excluded: HashSet<i64> = options.exclude_record_ids.iter().copied().collect();
+
+    let rows = sqlx::query(
+        r#"
+        SELECT r.id AS record_id, r.gesture_type, r.corrected_type, r.confidence, r.detected_at,
+               k.keypoints, k.frame_width, k.frame_height
+        FROM gesture_records r
+        JOIN keypoint_data k ON k.record_id = r.id
+        WHERE (? IS NULL OR r.confidence >= ?)
+          AND (? IS NULL OR r.confidence <= ?)
+          AND (? IS NULL OR r.detected_at >= ?)
+          AND (? IS NULL OR r.detected_at <= ?)
+        ORDER BY r.detected_at, r.id, k.id
+        "#,
+    )
+    .bind(options.min_confidence)
+    .bind(options.min_confidence)
+    .bind(options.max_confidence)
+    .bind(options.max_confidence)
+    .bind(&start)
+    .bind(&start)
+    .bind(&end)
+    .bind(&end)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut writer = DatasetWriter::create(options.format, &path)?;
+    let mut result = DatasetExportResult {
+        path: path.to_string_lossy().to_string(),
+        rows: 0,
+        records: 0,
+        relabeled: 0,
+        corrected: 0,
+        skipped: 0,
+    };
+    let mut exported_records = HashSet::new();
+    let mut relabeled_records = HashSet::new();
+    let mut corrected_records = HashSet::new();
+
+    for row in rows {
+        let record_id: i64 = row.get("record_id");
+        let gesture_type: String = row.get("gesture_type");
+        if excluded.contains(&record_id) {
+            continue;
+        }
+        // 按类型过滤时以原始识别结果为准，重新标注只影响导出的标签
+        if gesture_types.as_ref().is_some_and(|types| !types.contains(gesture_type.as_str())) {
+            continue;
+        }
+
+        let keypoints: Vec<u8> = row.get("keypoints");
+        let landmarks = match decode_landmarks(&keypoints) {
+            Ok(landmarks) if landmarks.len() == HAND_LANDMARK_COUNT => landmarks,
+            _ => {
+                result.skipped += 1;
+                continue;
+            }
+        };
+
+        // 标签优先级：导出时重新标注 > 用户纠正 > 识别结果
+        let corrected_type: Option<String> = row.get("corrected_type");
+        let label = match options.relabel.get(&record_id) {
+            Some(label) => {
+                relabeled_records.insert(record_id);
+                label.as_str()
+            }
+            None => match corrected_type.as_deref() {
+                Some(corrected) => {
+                    corrected_records.insert(record_id);
+                    corrected
+                }
+                None => gesture_type.as_str(),
+            },
+        };
+        let detected_at: String = row.get("detected_at");
+
+        writer.write(&DatasetRow {
+            record_id,
+            label,
+            confidence: row.get("confidence"),
+            detected_at: &detected_at,
+            frame_width: row.get("frame_width"),
+            frame_height: row.get("frame_height"),
+            landmarks: landmarks.iter().map(|p| [p.x, p.y, p.z]).collect(),
+        })?;
+        result.rows += 1;
+        exported_records.insert(record_id);
+    }
+
+    writer.finish()?;
+    result.records = exported_records.len();
+    result.relabeled = relabeled_records.len();
+    result.corrected = corrected_records.len();
+
+    Ok(result)
+}
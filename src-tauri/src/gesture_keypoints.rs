@@ -0,0 +1,167 @@
+// 关键点数据校验与压缩存储
+// 关键点使用MediaPipe归一化坐标：x、y为相对画面宽高的比例，z为相对深度
+use serde::Serialize;
+use std::fmt;
+
+use crate::gesture_service::{Landmark, HAND_LANDMARK_COUNT};
+
+// 前端提交的关键点JSON的最大长度
+pub const MAX_KEYPOINT_PAYLOAD_BYTES: usize = 16 * 1024;
+
+// 允许关键点略微超出画面（手部边缘被裁切时MediaPipe会给出画面外的估计值）
+const FRAME_MARGIN: f64 = 0.1;
+// z为相对深度，正常范围远小于1
+const MAX_DEPTH: f64 = 1.0;
+
+// 二进制格式：[版本][点数][每个点x/y/z各一个i16，小端]，坐标按1/10000定点量化
+const ENCODING_VERSION: u8 = 1;
+const QUANTIZE_SCALE: f64 = 10000.0;
+
+// 关键点校验错误
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum KeypointError {
+    PayloadTooLarge { bytes: usize, max: usize },
+    InvalidJson { message: String },
+    WrongPointCount { expected: usize, actual: usize },
+    NonFinite { index: usize },
+    OutOfBounds { index: usize },
+    MissingFrameSize,
+    InvalidFrameSize { width: i32, height: i32 },
+    Corrupted,
+}
+
+impl fmt::Display for KeypointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeypointError::PayloadTooLarge { bytes, max } => write!(f, "关键点数据过大: {}字节，上限{}字节", bytes, max),
+            KeypointError::InvalidJson { message } => write!(f, "关键点数据格式错误: {}", message),
+            KeypointError::WrongPointCount { expected, actual } => write!(f, "需要{}个关键点，实际为{}", expected, actual),
+            KeypointError::NonFinite { index } => write!(f, "第{}个关键点包含无效数值", index),
+            KeypointError::OutOfBounds { index } => write!(f, "第{}个关键点超出画面范围", index),
+            KeypointError::MissingFrameSize => write!(f, "提交关键点时需要提供画面宽高"),
+            KeypointError::InvalidFrameSize { width, height } => write!(f, "画面尺寸无效: {}x{}", width, height),
+            KeypointError::Corrupted => write!(f, "关键点数据已损坏"),
+        }
+    }
+}
+
+impl std::error::Error for KeypointError {}
+
+// save_gesture_record的错误：关键点校验失败时返回具体原因，其余为内部错误
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "detail", rename_all = "snake_case")]
+pub enum GestureRecordError {
+    InvalidKeypoints(KeypointError),
+    Internal(String),
+}
+
+impl fmt::Display for GestureRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GestureRecordError::InvalidKeypoints(e) => e.fmt(f),
+            GestureRecordError::Internal(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<KeypointError> for GestureRecordError {
+    fn from(e: KeypointError) -> Self {
+        GestureRecordError::InvalidKeypoints(e)
+    }
+}
+
+impl From<String> for GestureRecordError {
+    fn from(message: String) -> Self {
+        GestureRecordError::Internal(message)
+    }
+}
+
+impl From<&str> for GestureRecordError {
+    fn from(message: &str) -> Self {
+        GestureRecordError::Internal(message.to_string())
+    }
+}
+
+// 校验关键点：21个点、数值有限、位于画面范围内
+pub fn validate_landmarks(landmarks: &[Landmark]) -> Result<(), KeypointError> {
+    if landmarks.len() != HAND_LANDMARK_COUNT {
+        return Err(KeypointError::WrongPointCount {
+            expected: HAND_LANDMARK_COUNT,
+            actual: landmarks.len(),
+        });
+    }
+
+    let in_frame = |v: f64| (-FRAME_MARGIN..=1.0 + FRAME_MARGIN).contains(&v);
+    for (index, point) in landmarks.iter().enumerate() {
+        if !(point.x.is_finite() && point.y.is_finite() && point.z.is_finite()) {
+            return Err(KeypointError::NonFinite { index });
+        }
+        if !in_frame(point.x) || !in_frame(point.y) || point.z.abs() > MAX_DEPTH {
+            return Err(KeypointError::OutOfBounds { index });
+        }
+    }
+
+    Ok(())
+}
+
+// 解析并校验前端提交的关键点JSON
+pub fn parse_keypoint_payload(
+    payload: &str,
+    frame_width: Option<i32>,
+    frame_height: Option<i32>,
+) -> Result<(Vec<Landmark>, i32, i32), KeypointError> {
+    if payload.len() > MAX_KEYPOINT_PAYLOAD_BYTES {
+        return Err(KeypointError::PayloadTooLarge {
+            bytes: payload.len(),
+            max: MAX_KEYPOINT_PAYLOAD_BYTES,
+        });
+    }
+
+    let (Some(width), Some(height)) = (frame_width, frame_height) else {
+        return Err(KeypointError::MissingFrameSize);
+    };
+    if width <= 0 || height <= 0 {
+        return Err(KeypointError::InvalidFrameSize { width, height });
+    }
+
+    let landmarks: Vec<Landmark> = serde_json::from_str(payload)
+        .map_err(|e| KeypointError::InvalidJson { message: e.to_string() })?;
+    validate_landmarks(&landmarks)?;
+
+    Ok((landmarks, width, height))
+}
+
+fn quantize(value: f64) -> i16 {
+    (value * QUANTIZE_SCALE).round().clamp(i16::MIN as f64, i16::MAX as f64) as i16
+}
+
+// 编码为紧凑的二进制格式，21个点共128字节
+pub fn encode_landmarks(landmarks: &[Landmark]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(2 + landmarks.len() * 6);
+    bytes.push(ENCODING_VERSION);
+    bytes.push(landmarks.len() as u8);
+    for point in landmarks {
+        for value in [point.x, point.y, point.z] {
+            bytes.extend_from_slice(&quantize(value).to_le_bytes());
+        }
+    }
+    bytes
+}
+
+// 解码数据库中的关键点，兼容旧版本以JSON文本保存的数据
+pub fn decode_landmarks(bytes: &[u8]) -> Result<Vec<Landmark>, KeypointError> {
+    match bytes {
+        [ENCODING_VERSION, count, values @ ..] if values.len() == *count as usize * 6 => Ok(values
+            .chunks_exact(6)
+            .map(|chunk| {
+                let value = |i: usize| i16::from_le_bytes([chunk[i], chunk[i + 1]]) as f64 / QUANTIZE_SCALE;
+                Landmark { x: value(0), y: value(2), z: value(4) }
+            })
+            .collect()),
+        _ => {
+            let text = std::str::from_utf8(bytes).map_err(|_| KeypointError::Corrupted)?;
+            serde_json::from_str(text).map_err(|_| KeypointError::Corrupted)
+        }
+    }
+}
@@ -0,0 +1,323 @@
+// 动态手势识别 - 根据连续多帧关键点的运动轨迹识别挥手、滑动等手势
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::gesture_service::{combine, ramp, GestureClassification, Landmark, HAND_LANDMARK_COUNT};
+
+pub const MOTION_GESTURE_EVENT: &str = "gesture://motion";
+
+// gesture_configs.kind 的取值
+pub const GESTURE_KIND_STATIC: &str = "static";
+pub const GESTURE_KIND_DYNAMIC: &str = "dynamic";
+
+// 每个识别会话独立的运动轨迹
+pub static MOTION_SESSIONS: Lazy<Mutex<HashMap<String, MotionTracker>>> = Lazy::new(|| {
+    Mutex::new(HashMap::new())
+});
+
+// 手腕与四指根部，取平均作为手掌中心
+const PALM_POINTS: [usize; 5] = [0, 5, 9, 13, 17];
+const WRIST: usize = 0;
+const THUMB_TIP: usize = 4;
+const INDEX_TIP: usize = 8;
+const MIDDLE_MCP: usize = 9;
+
+// 一段运动至少需要的帧数
+const MIN_MOTION_FRAMES: usize = 6;
+// 运动速度（手掌尺寸/秒）高于该值视为在运动
+const MOTION_SPEED: f64 = 1.5;
+// 静止超过该时长视为一段运动结束
+const STILL_DURATION: Duration = Duration::from_millis(150);
+// 单段运动最长保留的时长
+const MAX_MOTION_DURATION: Duration = Duration::from_millis(2500);
+// 轨迹中小于该长度的位移视为抖动，不参与转角计算
+const MIN_STEP: f64 = 0.05;
+// 挥手时判定一次折返所需的回程距离（手掌尺寸）
+const WAVE_REVERSAL: f64 = 0.3;
+// 低于该置信度的结果视为unknown
+const MIN_MOTION_CONFIDENCE: f64 = 0.5;
+
+// 一段运动的识别结果
+#[derive(Debug, Clone)]
+pub struct MotionDetection {
+    pub classification: GestureClassification,
+    pub duration_ms: i64,
+}
+
+#[derive(Debug, Clone)]
+struct MotionFrame {
+    at: Instant,
+    landmarks: Vec<Landmark>,
+}
+
+// 按运动/静止切分连续帧，每段运动结束时进行一次识别
+#[derive(Debug, Clone, Default)]
+pub struct MotionTracker {
+    frames: VecDeque<MotionFrame>,
+    moving: bool,
+    still_since: Option<Instant>,
+    last_seen: Option<Instant>,
+}
+
+impl MotionTracker {
+    // 输入一帧关键点，一段运动结束时返回识别结果
+    pub fn push(&mut self, landmarks: Vec<Landmark>, now: Instant) -> Option<MotionDetection> {
+        if landmarks.len() != HAND_LANDMARK_COUNT {
+            return None;
+        }
+        self.last_seen = Some(now);
+
+        let speed = self.frames.back().map(|last| {
+            let elapsed = now.duration_since(last.at).as_secs_f64().max(1e-3);
+            frame_activity(&last.landmarks, &landmarks) / elapsed
+        });
+        self.frames.push_back(MotionFrame { at: now, landmarks });
+
+        let speed = speed?;
+
+        if !self.moving {
+            if speed >= MOTION_SPEED {
+                self.moving = true;
+                self.still_since = None;
+            } else {
+                // 静止时只保留最新一帧作为下一段运动的起点
+                while self.frames.len() > 1 {
+                    self.frames.pop_front();
+                }
+            }
+            return None;
+        }
+
+        while let Some(front) = self.frames.front() {
+            if now.duration_since(front.at) > MAX_MOTION_DURATION {
+                self.frames.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if speed >= MOTION_SPEED {
+            self.still_since = None;
+            return None;
+        }
+
+        let still_since = *self.still_since.get_or_insert(now);
+        if now.duration_since(still_since) >= STILL_DURATION {
+            return self.finish();
+        }
+        None
+    }
+
+    // 长时间没有新帧
+    pub fn is_idle(&self, now: Instant, timeout: Duration) -> bool {
+        self.last_seen.map_or(true, |at| now.duration_since(at) >= timeout)
+    }
+
+    // 手离开画面或会话结束时，识别尚未结束的运动
+    pub fn flush(&mut self) -> Option<MotionDetection> {
+        if self.moving {
+            self.finish()
+        } else {
+            self.frames.clear();
+            None
+        }
+    }
+
+    fn finish(&mut self) -> Option<MotionDetection> {
+        let frames: Vec<MotionFrame> = self.frames.drain(..).collect();
+        self.moving = false;
+        self.still_since = None;
+
+        // 保留最后一帧作为下一段运动的起点
+        if let Some(last) = frames.last() {
+            self.frames.push_back(last.clone());
+        }
+
+        let (first, last) = (frames.first()?, frames.last()?);
+        let duration_ms = last.at.duration_since(first.at).as_millis() as i64;
+        let sequence: Vec<Vec<Landmark>> = frames.into_iter().map(|frame| frame.landmarks).collect();
+        let classification = classify_motion(&sequence).ok()?;
+        if classification.gesture_type == "unknown" {
+            return None;
+        }
+
+        Some(MotionDetection { classification, duration_ms })
+    }
+}
+
+fn palm_center(lm: &[Landmark]) -> (f64, f64) {
+    let n = PALM_POINTS.len() as f64;
+    let x = PALM_POINTS.iter().map(|&i| lm[i].x).sum::<f64>() / n;
+    let y = PALM_POINTS.iter().map(|&i| lm[i].y).sum::<f64>() / n;
+    (x, y)
+}
+
+fn planar_distance(a: &Landmark, b: &Landmark) -> f64 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+fn palm_size(lm: &[Landmark]) -> f64 {
+    planar_distance(&lm[WRIST], &lm[MIDDLE_MCP])
+}
+
+// 拇指尖与食指尖的张开程度（手掌尺寸）
+fn pinch_ratio(lm: &[Landmark]) -> f64 {
+    planar_distance(&lm[THUMB_TIP], &lm[INDEX_TIP]) / palm_size(lm).max(f64::EPSILON)
+}
+
+// 相邻两帧间手掌移动与捏合变化的幅度，用于切分运动段
+fn frame_activity(previous: &[Landmark], current: &[Landmark]) -> f64 {
+    let palm = palm_size(current).max(f64::EPSILON);
+    let (px, py) = palm_center(previous);
+    let (cx, cy) = palm_center(current);
+    let movement = ((cx - px).powi(2) + (cy - py).powi(2)).sqrt() / palm;
+    movement + (pinch_ratio(current) - pinch_ratio(previous)).abs()
+}
+
+// 统计水平方向的折返次数，回程超过WAVE_REVERSAL才算一次
+fn count_reversals(xs: &[f64]) -> usize {
+    let mut reversals = 0;
+    let mut direction = 0.0;
+    let mut extreme = xs.first().copied().unwrap_or(0.0);
+
+    for &x in xs {
+        let delta = x - extreme;
+        if direction == 0.0 {
+            if delta.abs() >= WAVE_REVERSAL {
+                direction = delta.signum();
+                extreme = x;
+            }
+        } else if delta * direction > 0.0 {
+            extreme = x;
+        } else if delta.abs() >= WAVE_REVERSAL {
+            reversals += 1;
+            direction = -direction;
+            extreme = x;
+        }
+    }
+
+    reversals
+}
+
+// 轨迹累计转过的角度（度），方向相反的转动相互抵消
+fn total_turning(points: &[(f64, f64)]) -> f64 {
+    let mut steps = Vec::new();
+    let mut anchor = points[0];
+    for &point in &points[1..] {
+        let step = (point.0 - anchor.0, point.1 - anchor.1);
+        if (step.0.powi(2) + step.1.powi(2)).sqrt() >= MIN_STEP {
+            steps.push(step);
+            anchor = point;
+        }
+    }
+
+    steps
+        .windows(2)
+        .map(|pair| {
+            let (a, b) = (pair[0], pair[1]);
+            let cross = a.0 * b.1 - a.1 * b.0;
+            let dot = a.0 * b.0 + a.1 * b.1;
+            cross.atan2(dot).to_degrees()
+        })
+        .sum::<f64>()
+        .abs()
+}
+
+fn median_of(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted[sorted.len() / 2]
+}
+
+// 计算每种动态手势的置信度
+// 坐标为画面坐标，x增大为向右；镜像显示时左右由前端自行对应
+pub fn score_motion(frames: &[Vec<Landmark>]) -> Result<BTreeMap<String, f64>, String> {
+    if frames.len() < MIN_MOTION_FRAMES {
+        return Err(format!("至少需要{}帧关键点，实际为{}", MIN_MOTION_FRAMES, frames.len()));
+    }
+    if let Some(frame) = frames.iter().find(|frame| frame.len() != HAND_LANDMARK_COUNT) {
+        return Err(format!("每帧需要{}个关键点，实际为{}", HAND_LANDMARK_COUNT, frame.len()));
+    }
+
+    // 以整段的平均手掌尺寸为单位，使特征与手离镜头的远近无关
+    let palm = frames.iter().map(|frame| palm_size(frame)).sum::<f64>() / frames.len() as f64;
+    if !palm.is_finite() || palm <= f64::EPSILON {
+        return Err("关键点数据无效".to_string());
+    }
+
+    let origin = palm_center(&frames[0]);
+    let points: Vec<(f64, f64)> = frames
+        .iter()
+        .map(|frame| {
+            let (x, y) = palm_center(frame);
+            ((x - origin.0) / palm, (y - origin.1) / palm)
+        })
+        .collect();
+
+    let path_length: f64 = points
+        .windows(2)
+        .map(|pair| ((pair[1].0 - pair[0].0).powi(2) + (pair[1].1 - pair[0].1).powi(2)).sqrt())
+        .sum();
+    let (dx, dy) = points[points.len() - 1];
+    let net = (dx.powi(2) + dy.powi(2)).sqrt();
+    let straightness = if path_length > f64::EPSILON { net / path_length } else { 0.0 };
+
+    let xs: Vec<f64> = points.iter().map(|p| p.0).collect();
+    let x_range = xs.iter().cloned().fold(f64::MIN, f64::max) - xs.iter().cloned().fold(f64::MAX, f64::min);
+
+    let pinches: Vec<f64> = frames.iter().map(|frame| pinch_ratio(frame)).collect();
+    let edge = (pinches.len() / 4).max(1);
+    let pinch_change = median_of(&pinches[pinches.len() - edge..]) - median_of(&pinches[..edge]);
+
+    let horizontal = 1.0 - ramp(dy.abs() / dx.abs().max(f64::EPSILON), 0.4, 0.8);
+    let steady = 1.0 - ramp(path_length, 1.0, 2.0);
+
+    let mut scores = BTreeMap::new();
+    scores.insert("swipe_right".to_string(), combine(&[ramp(dx, 1.0, 2.5), ramp(straightness, 0.6, 0.9), horizontal]));
+    scores.insert("swipe_left".to_string(), combine(&[ramp(-dx, 1.0, 2.5), ramp(straightness, 0.6, 0.9), horizontal]));
+    scores.insert("wave".to_string(), combine(&[
+        ramp(count_reversals(&xs) as f64, 1.0, 3.0),
+        ramp(x_range, 0.6, 1.2),
+        1.0 - ramp(straightness, 0.3, 0.6),
+    ]));
+    scores.insert("circle".to_string(), combine(&[
+        ramp(total_turning(&points), 240.0, 330.0),
+        ramp(path_length, 2.0, 4.0),
+        1.0 - ramp(straightness, 0.2, 0.5),
+    ]));
+    scores.insert("zoom_in".to_string(), combine(&[ramp(pinch_change, 0.3, 0.8), steady]));
+    scores.insert("zoom_out".to_string(), combine(&[ramp(-pinch_change, 0.3, 0.8), steady]));
+
+    Ok(scores)
+}
+
+// 对一段关键点序列进行动态手势分类
+pub fn classify_motion(frames: &[Vec<Landmark>]) -> Result<GestureClassification, String> {
+    let scores = score_motion(frames)?;
+
+    let (gesture_type, confidence) = scores
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(gesture, score)| (gesture.clone(), *score))
+        .unwrap_or_else(|| ("unknown".to_string(), 0.0));
+
+    let gesture_type = if confidence < MIN_MOTION_CONFIDENCE {
+        "unknown".to_string()
+    } else {
+        gesture_type
+    };
+
+    Ok(GestureClassification {
+        gesture_type,
+        confidence,
+        scores,
+    })
+}
+
+// Tauri命令：识别一段关键点序列中的动态手势
+#[tauri::command]
+pub async fn classify_keypoint_sequence(frames: Vec<Vec<Landmark>>) -> Result<GestureClassification, String> {
+    classify_motion(&frames)
+}
@@ -0,0 +1,264 @@
+// 手势数据保留策略 - 定期清理过期记录与关键点，并提供按条件删除
+use serde::{Deserialize, Serialize};
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::{Row, Sqlite, SqlitePool};
+use std::time::Duration;
+
+use crate::gesture_stats::{DateRange, RANGE_FILTER};
+use crate::services::DatabaseService;
+
+// 保留策略对应的设置项，值为0或空表示不限制
+pub const RETENTION_MAX_AGE_SETTING: &str = "gesture_retention_days";
+pub const RETENTION_MAX_RECORDS_SETTING: &str = "gesture_retention_max_records";
+pub const RETENTION_KEYPOINT_RECORDS_SETTING: &str = "gesture_retention_keypoint_records";
+
+// 后台清理的间隔
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+// 单条DELETE语句中IN列表的最大长度，避免超过SQLite的参数上限
+const DELETE_CHUNK_SIZE: usize = 500;
+
+// 保留策略，None表示不限制
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct GestureRetentionPolicy {
+    pub max_age_days: Option<i64>,
+    pub max_records: Option<i64>,
+    pub keypoint_records: Option<i64>, // 只为最新的N条记录保留关键点
+}
+
+// 删除或清理的结果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GestureDeleteResult {
+    pub records: u64,
+    pub keypoints: u64,
+}
+
+fn parse_limit(value: Option<String>) -> Option<i64> {
+    value
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|v| *v > 0)
+}
+
+fn format_limit(value: Option<i64>) -> String {
+    value.filter(|v| *v > 0).map(|v| v.to_string()).unwrap_or_default()
+}
+
+pub async fn load_retention_policy(db: &DatabaseService) -> anyhow::Result<GestureRetentionPolicy> {
+    Ok(GestureRetentionPolicy {
+        max_age_days: parse_limit(db.get_setting(RETENTION_MAX_AGE_SETTING).await?),
+        max_records: parse_limit(db.get_setting(RETENTION_MAX_RECORDS_SETTING).await?),
+        keypoint_records: parse_limit(db.get_setting(RETENTION_KEYPOINT_RECORDS_SETTING).await?),
+    })
+}
+
+// 删除指定记录及其关键点，模板和动作日志中的引用置空
+pub async fn delete_records_by_ids(pool: &SqlitePool, ids: &[i64]) -> Result<GestureDeleteResult, sqlx::Error> {
+    let mut result = GestureDeleteResult::default();
+    let mut tx = pool.begin().await?;
+
+    for chunk in ids.chunks(DELETE_CHUNK_SIZE) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let statements = [
+            format!("UPDATE gesture_templates SET record_id = NULL WHERE record_id IN ({})", placeholders),
+            format!("UPDATE gesture_action_logs SET record_id = NULL WHERE record_id IN ({})", placeholders),
+            format!("DELETE FROM keypoint_data WHERE record_id IN ({})", placeholders),
+            format!("DELETE FROM gesture_records WHERE id IN ({})", placeholders),
+        ];
+
+        for (index, sql) in statements.iter().enumerate() {
+            let mut query = sqlx::query(sql);
+            for id in chunk {
+                query = query.bind(id);
+            }
+            let affected = query.execute(&mut *tx).await?.rows_affected();
+            match index {
+                2 => result.keypoints += affected,
+                3 => result.records += affected,
+                _ => {}
+            }
+        }
+    }
+
+    tx.commit().await?;
+    Ok(result)
+}
+
+async fn fetch_ids<'q>(query: Query<'q, Sqlite, SqliteArguments<'q>>, pool: &SqlitePool) -> Result<Vec<i64>, sqlx::Error> {
+    Ok(query
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| row.get("id"))
+        .collect())
+}
+
+// 按保留策略清理：先删除过期和超出数量的记录，再删除较旧记录的关键点
+pub async fn enforce_retention(pool: &SqlitePool, policy: &GestureRetentionPolicy) -> Result<GestureDeleteResult, sqlx::Error> {
+    let mut ids = Vec::new();
+
+    if let Some(days) = policy.max_age_days {
+        ids.extend(fetch_ids(
+            sqlx::query("SELECT id FROM gesture_records WHERE detected_at < datetime('now', ?)")
+                .bind(format!("-{} days", days)),
+            pool,
+        ).await?);
+    }
+
+    if let Some(max_records) = policy.max_records {
+        ids.extend(fetch_ids(
+            sqlx::query("SELECT id FROM gesture_records ORDER BY detected_at DESC, id DESC LIMIT -1 OFFSET ?")
+                .bind(max_records),
+            pool,
+        ).await?);
+    }
+
+    ids.sort_unstable();
+    ids.dedup();
+    let mut result = delete_records_by_ids(pool, &ids).await?;
+
+    if let Some(keypoint_records) = policy.keypoint_records {
+        result.keypoints += sqlx::query(
+            r#"
+            DELETE FROM keypoint_data
+            WHERE record_id NOT IN (
+                SELECT id FROM gesture_records ORDER BY detected_at DESC, id DESC LIMIT ?
+            )
+            "#,
+        )
+        .bind(keypoint_records)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+
+    Ok(result)
+}
+
+// 启动定期清理任务，启动时立即执行一次；每次重新读取设置，修改后下一轮生效
+pub fn start_retention_job(db: DatabaseService) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+        loop {
+            interval.tick().await;
+            let policy = match load_retention_policy(&db).await {
+                Ok(policy) => policy,
+                Err(e) => {
+                    eprintln!("读取手势保留策略失败: {}", e);
+                    continue;
+                }
+            };
+            match enforce_retention(db.get_pool(), &policy).await {
+                Ok(result) if result.records > 0 || result.keypoints > 0 => {
+                    println!("已清理手势记录{}条，关键点{}条", result.records, result.keypoints);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("清理手势数据失败: {}", e),
+            }
+        }
+    });
+}
+
+// Tauri命令：获取保留策略
+#[tauri::command]
+pub async fn get_gesture_retention_policy() -> Result<GestureRetentionPolicy, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    load_retention_policy(&db).await.map_err(|e| e.to_string())
+}
+
+// Tauri命令：更新保留策略，后台任务下一轮按新策略清理
+#[tauri::command]
+pub async fn set_gesture_retention_policy(policy: GestureRetentionPolicy) -> Result<GestureRetentionPolicy, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    for (key, value) in [
+        (RETENTION_MAX_AGE_SETTING, policy.max_age_days),
+        (RETENTION_MAX_RECORDS_SETTING, policy.max_records),
+        (RETENTION_KEYPOINT_RECORDS_SETTING, policy.keypoint_records),
+    ] {
+        db.set_setting(key, &format_limit(value))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    load_retention_policy(&db).await.map_err(|e| e.to_string())
+}
+
+// Tauri命令：立即按保留策略清理一次
+#[tauri::command]
+pub async fn run_gesture_retention() -> Result<GestureDeleteResult, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    let policy = load_retention_policy(&db).await.map_err(|e| e.to_string())?;
+    enforce_retention(db.get_pool(), &policy).await.map_err(|e| e.to_string())
+}
+
+// Tauri命令：按ID删除手势记录
+#[tauri::command]
+pub async fn delete_gesture_records(ids: Vec<i64>) -> Result<GestureDeleteResult, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    delete_records_by_ids(db.get_pool(), &ids).await.map_err(|e| e.to_string())
+}
+
+// Tauri命令：删除某种手势的全部记录
+#[tauri::command]
+pub async fn delete_gesture_records_by_type(gesture_type: String) -> Result<GestureDeleteResult, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+
+    let ids = fetch_ids(
+        sqlx::query("SELECT id FROM gesture_records WHERE gesture_type = ?").bind(&gesture_type),
+        pool,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    delete_records_by_ids(pool, &ids).await.map_err(|e| e.to_string())
+}
+
+// Tauri命令：删除时间范围内的记录，至少需要指定一端
+#[tauri::command]
+pub async fn delete_gesture_records_in_range(
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<GestureDeleteResult, String> {
+    use crate::DATABASE;
+    if start.is_none() && end.is_none() {
+        return Err("需要指定开始或结束时间".to_string());
+    }
+
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let range = DateRange::new(start, end);
+
+    let sql = format!("SELECT id FROM gesture_records WHERE {}", RANGE_FILTER);
+    let ids = fetch_ids(range.bind(sqlx::query(&sql)), pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    delete_records_by_ids(pool, &ids).await.map_err(|e| e.to_string())
+}
@@ -1,7 +1,40 @@
 // 手势识别服务 - Tauri后端
 use serde::{Deserialize, Serialize};
 use sqlx::{Row, SqlitePool};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+use crate::gesture_keypoints::{decode_landmarks, encode_landmarks, parse_keypoint_payload, GestureRecordError};
+use crate::gesture_stats::{DateRange, RANGE_FILTER};
+use crate::gesture_retention::delete_records_by_ids;
+use crate::gesture_templates::{delete_config_templates, reload_template_cache, score_custom_gestures};
+use crate::gesture_motion::{MotionDetection, GESTURE_KIND_DYNAMIC, GESTURE_KIND_STATIC, MOTION_GESTURE_EVENT, MOTION_SESSIONS};
+use crate::gesture_smoothing::{SmoothedGestureEvent, GESTURE_END_EVENT, GESTURE_SESSIONS, GESTURE_START_EVENT, SESSION_IDLE_TIMEOUT};
+
+pub const GESTURE_RECOGNITION_EVENT: &str = "gesture://recognition";
+pub const GESTURE_RECOGNITION_SETTING: &str = "gesture_recognition_enabled";
+
+// 手势识别开关，关闭期间不保存记录也不触发动作
+pub static GESTURE_RECOGNITION_ENABLED: AtomicBool = AtomicBool::new(true);
+
+// 启动时从设置恢复手势识别开关
+pub async fn load_gesture_recognition_state(db: &crate::services::DatabaseService) -> anyhow::Result<()> {
+    let enabled = db.get_setting(GESTURE_RECOGNITION_SETTING)
+        .await?
+        .map_or(true, |value| value != "false");
+    GESTURE_RECOGNITION_ENABLED.store(enabled, Ordering::Relaxed);
+    Ok(())
+}
+
+// 切换手势识别开关并保存到设置，返回切换后的状态
+pub async fn toggle_gesture_recognition(app: &tauri::AppHandle, db: &crate::services::DatabaseService) -> anyhow::Result<bool> {
+    let enabled = !GESTURE_RECOGNITION_ENABLED.fetch_xor(true, Ordering::Relaxed);
+    db.set_setting(GESTURE_RECOGNITION_SETTING, &enabled.to_string()).await?;
+    let _ = app.emit(GESTURE_RECOGNITION_EVENT, enabled);
+    Ok(enabled)
+}
 
 // 手势配置结构
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -11,10 +44,18 @@ pub struct GestureConfig {
     pub gesture_type: String,
     pub threshold: f64,
     pub enabled: bool,
+    #[serde(default)]
+    pub custom: bool, // 用户录入样本训练的自定义手势
+    #[serde(default = "default_gesture_kind")]
+    pub kind: String, // static：静态手势；dynamic：基于运动轨迹的动态手势
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
 
+fn default_gesture_kind() -> String {
+    GESTURE_KIND_STATIC.to_string()
+}
+
 // 手势记录结构
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GestureRecord {
@@ -23,6 +64,11 @@ pub struct GestureRecord {
     pub confidence: f64,
     pub detected_at: String,
     pub config_id: Option<i64>,
+    pub threshold: Option<f64>, // 识别时配置的阈值
+    pub ended_at: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub corrected_type: Option<String>,
+    pub corrected_at: Option<String>,
 }
 
 // 关键点数据结构
@@ -30,12 +76,20 @@ pub struct GestureRecord {
 pub struct KeypointData {
     pub id: Option<i64>,
     pub record_id: i64,
-    pub keypoints: String, // JSON格式的关键点数据
+    pub keypoints: Vec<Landmark>, // 数据库中以量化后的二进制保存，读取时解码为关键点数组
     pub frame_width: i32,
     pub frame_height: i32,
     pub created_at: Option<String>,
 }
 
+// 带关键点数据的手势记录，用于回放
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestureRecordKeypoints {
+    #[serde(flatten)]
+    pub record: GestureRecord,
+    pub keypoints: Vec<KeypointData>,
+}
+
 // 手势统计结构
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GestureStats {
@@ -45,6 +99,42 @@ pub struct GestureStats {
     pub last_detection: Option<String>,
 }
 
+// 单个手部关键点（MediaPipe归一化坐标）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Landmark {
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub z: f64,
+}
+
+// 关键点分类结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestureClassification {
+    pub gesture_type: String,
+    pub confidence: f64,
+    pub scores: BTreeMap<String, f64>,
+}
+
+// 动态手势事件的推送内容
+#[derive(Debug, Serialize, Clone)]
+pub struct MotionGestureEventPayload {
+    pub session_id: String,
+    pub record_id: i64,
+    pub gesture_type: String,
+    pub confidence: f64,
+    pub duration_ms: i64,
+}
+
+// 稳定手势事件的推送内容
+#[derive(Debug, Serialize, Clone)]
+pub struct GestureEventPayload {
+    pub session_id: String,
+    pub record_id: Option<i64>,
+    #[serde(flatten)]
+    pub event: SmoothedGestureEvent,
+}
+
 // 初始化手势数据库表
 pub async fn init_gesture_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     // 创建手势配置表
@@ -56,6 +146,8 @@ pub async fn init_gesture_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             gesture_type VARCHAR(20) NOT NULL,
             threshold REAL DEFAULT 0.8,
             enabled BOOLEAN DEFAULT true,
+            custom BOOLEAN DEFAULT false,
+            kind VARCHAR(10) DEFAULT 'static',
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
         )
@@ -82,6 +174,11 @@ pub async fn init_gesture_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             confidence REAL NOT NULL,
             detected_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             config_id INTEGER,
+            threshold REAL,
+            ended_at DATETIME,
+            duration_ms INTEGER,
+            corrected_type VARCHAR(20), -- 用户纠正后的手势，为空表示未纠正
+            corrected_at DATETIME,
             FOREIGN KEY (config_id) REFERENCES gesture_configs(id)
         )
         "#,
@@ -104,7 +201,7 @@ pub async fn init_gesture_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         CREATE TABLE IF NOT EXISTS keypoint_data (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             record_id INTEGER NOT NULL,
-            keypoints TEXT NOT NULL,
+            keypoints BLOB NOT NULL,
             frame_width INTEGER NOT NULL,
             frame_height INTEGER NOT NULL,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
@@ -137,9 +234,27 @@ async fn insert_default_configs(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         ("三", "three", 0.80),
         ("点赞", "thumbup", 0.90),
         ("耶", "yeah", 0.75),
+        ("二", "two", 0.80),
+    ];
+    let dynamic_configs = vec![
+        ("左滑", "swipe_left", 0.75),
+        ("右滑", "swipe_right", 0.75),
+        ("挥手", "wave", 0.70),
+        ("双指放大", "zoom_in", 0.75),
+        ("双指缩小", "zoom_out", 0.75),
+        ("画圈", "circle", 0.70),
     ];
 
-    for (name, gesture_type, threshold) in default_configs {
+    let configs = default_configs
+        .into_iter()
+        .map(|(name, gesture_type, threshold)| (name, gesture_type, threshold, GESTURE_KIND_STATIC))
+        .chain(
+            dynamic_configs
+                .into_iter()
+                .map(|(name, gesture_type, threshold)| (name, gesture_type, threshold, GESTURE_KIND_DYNAMIC)),
+        );
+
+    for (name, gesture_type, threshold, kind) in configs {
         // 检查是否已存在
         let exists = sqlx::query("SELECT COUNT(*) as count FROM gesture_configs WHERE gesture_type = ?")
             .bind(gesture_type)
@@ -149,11 +264,12 @@ async fn insert_default_configs(pool: &SqlitePool) -> Result<(), sqlx::Error> {
 
         if !exists {
             sqlx::query(
-                "INSERT INTO gesture_configs (name, gesture_type, threshold) VALUES (?, ?, ?)"
+                "INSERT INTO gesture_configs (name, gesture_type, threshold, kind) VALUES (?, ?, ?, ?)"
             )
             .bind(name)
             .bind(gesture_type)
             .bind(threshold)
+            .bind(kind)
             .execute(pool)
             .await?;
         }
@@ -162,6 +278,199 @@ async fn insert_default_configs(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
+// ===== 基于21个手部关键点的静态手势分类 =====
+
+pub const HAND_LANDMARK_COUNT: usize = 21;
+
+// MediaPipe手部关键点索引
+const WRIST: usize = 0;
+const THUMB_CMC: usize = 1;
+const THUMB_MCP: usize = 2;
+const THUMB_IP: usize = 3;
+const THUMB_TIP: usize = 4;
+const INDEX_MCP: usize = 5;
+const INDEX_TIP: usize = 8;
+const MIDDLE_MCP: usize = 9;
+const MIDDLE_TIP: usize = 12;
+
+// 四指的 (MCP, PIP, DIP, TIP) 索引
+const FINGER_JOINTS: [[usize; 4]; 4] = [
+    [5, 6, 7, 8],
+    [9, 10, 11, 12],
+    [13, 14, 15, 16],
+    [17, 18, 19, 20],
+];
+
+// 低于该置信度的结果视为unknown
+const MIN_CLASSIFY_CONFIDENCE: f64 = 0.5;
+
+// 从关键点提取的手部特征，各分数范围均为0~1
+#[derive(Debug, Clone, Copy)]
+pub struct HandFeatures {
+    // 拇指、食指、中指、无名指、小指的伸展程度
+    pub extension: [f64; 5],
+    // 食指与中指的张开程度
+    pub spread: f64,
+    // 拇指朝上的程度
+    pub thumb_up: f64,
+    // 拇指尖与食指尖的捏合程度
+    pub pinch: f64,
+    // 食指尖离开掌心的程度
+    pub index_reach: f64,
+}
+
+fn distance(a: &Landmark, b: &Landmark) -> f64 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
+
+fn sub(a: &Landmark, b: &Landmark) -> [f64; 3] {
+    [a.x - b.x, a.y - b.y, a.z - b.z]
+}
+
+// 两个向量的夹角（度）
+fn vector_angle(v1: [f64; 3], v2: [f64; 3]) -> f64 {
+    let dot = v1[0] * v2[0] + v1[1] * v2[1] + v1[2] * v2[2];
+    let mag = (v1[0].powi(2) + v1[1].powi(2) + v1[2].powi(2)).sqrt()
+        * (v2[0].powi(2) + v2[1].powi(2) + v2[2].powi(2)).sqrt();
+    if mag == 0.0 {
+        return 0.0;
+    }
+    (dot / mag).clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+// 以b为顶点的夹角（度）
+fn angle_at(a: &Landmark, b: &Landmark, c: &Landmark) -> f64 {
+    vector_angle(sub(a, b), sub(c, b))
+}
+
+// 将value从[low, high]线性映射到[0, 1]
+pub(crate) fn ramp(value: f64, low: f64, high: f64) -> f64 {
+    ((value - low) / (high - low)).clamp(0.0, 1.0)
+}
+
+// 手指伸展程度：关节越直、指尖离手腕越远，分数越高
+fn finger_extension(lm: &[Landmark], [mcp, pip, dip, tip]: [usize; 4]) -> f64 {
+    let straightness = (angle_at(&lm[mcp], &lm[pip], &lm[dip]) + angle_at(&lm[pip], &lm[dip], &lm[tip])) / 2.0;
+    let reach = distance(&lm[WRIST], &lm[tip]) / distance(&lm[WRIST], &lm[pip]).max(f64::EPSILON);
+    0.6 * ramp(straightness, 100.0, 165.0) + 0.4 * ramp(reach, 0.95, 1.35)
+}
+
+// 拇指伸展程度：拇指弯曲幅度较小，额外参考指尖到食指根部的距离
+fn thumb_extension(lm: &[Landmark], palm_size: f64) -> f64 {
+    let straightness = (angle_at(&lm[THUMB_CMC], &lm[THUMB_MCP], &lm[THUMB_IP])
+        + angle_at(&lm[THUMB_MCP], &lm[THUMB_IP], &lm[THUMB_TIP])) / 2.0;
+    let reach = distance(&lm[THUMB_TIP], &lm[INDEX_MCP]) / palm_size;
+    0.5 * ramp(straightness, 130.0, 170.0) + 0.5 * ramp(reach, 0.3, 0.6)
+}
+
+pub fn extract_hand_features(lm: &[Landmark]) -> Result<HandFeatures, String> {
+    if lm.len() != HAND_LANDMARK_COUNT {
+        return Err(format!("需要{}个关键点，实际为{}", HAND_LANDMARK_COUNT, lm.len()));
+    }
+
+    // 以手腕到中指根部的距离作为尺度，使特征与手离镜头的远近无关
+    let palm_size = distance(&lm[WRIST], &lm[MIDDLE_MCP]);
+    if !palm_size.is_finite() || palm_size <= f64::EPSILON {
+        return Err("关键点数据无效".to_string());
+    }
+
+    let mut extension = [0.0; 5];
+    extension[0] = thumb_extension(lm, palm_size);
+    for (i, joints) in FINGER_JOINTS.iter().enumerate() {
+        extension[i + 1] = finger_extension(lm, *joints);
+    }
+
+    let spread_angle = vector_angle(sub(&lm[INDEX_TIP], &lm[INDEX_MCP]), sub(&lm[MIDDLE_TIP], &lm[MIDDLE_MCP]));
+
+    // 图像坐标y轴向下，拇指朝上时指尖的y小于根部
+    let thumb_len = distance(&lm[THUMB_MCP], &lm[THUMB_TIP]).max(f64::EPSILON);
+    let thumb_up_ratio = (lm[THUMB_MCP].y - lm[THUMB_TIP].y) / thumb_len;
+
+    let pinch_ratio = distance(&lm[THUMB_TIP], &lm[INDEX_TIP]) / palm_size;
+    let index_reach_ratio = distance(&lm[WRIST], &lm[INDEX_TIP]) / palm_size;
+
+    Ok(HandFeatures {
+        extension,
+        spread: ramp(spread_angle, 10.0, 25.0),
+        thumb_up: ramp(thumb_up_ratio, 0.3, 0.8),
+        pinch: 1.0 - ramp(pinch_ratio, 0.15, 0.4),
+        index_reach: ramp(index_reach_ratio, 0.9, 1.2),
+    })
+}
+
+// 综合多个0~1的匹配度：兼顾平均水平与最差的一项
+pub(crate) fn combine(agreements: &[f64]) -> f64 {
+    let mean = agreements.iter().sum::<f64>() / agreements.len() as f64;
+    let min = agreements.iter().cloned().fold(1.0, f64::min);
+    0.5 * mean + 0.5 * min
+}
+
+// 按期望的手指伸展状态（拇指到小指）计算匹配度
+fn finger_pattern(features: &HandFeatures, pattern: [bool; 5]) -> Vec<f64> {
+    features.extension
+        .iter()
+        .zip(pattern)
+        .map(|(e, extended)| if extended { *e } else { 1.0 - e })
+        .collect()
+}
+
+// 计算每种内置手势的置信度
+pub fn score_gestures(features: &HandFeatures) -> BTreeMap<String, f64> {
+    let pattern = |p: [bool; 5]| finger_pattern(features, p);
+    let with = |mut agreements: Vec<f64>, extra: &[f64]| {
+        agreements.extend_from_slice(extra);
+        combine(&agreements)
+    };
+
+    let mut scores = BTreeMap::new();
+    scores.insert("fist".to_string(), combine(&pattern([false, false, false, false, false])));
+    scores.insert("five".to_string(), combine(&pattern([true, true, true, true, true])));
+    scores.insert("one".to_string(), combine(&pattern([false, true, false, false, false])));
+    scores.insert("two".to_string(), with(pattern([false, true, true, false, false]), &[1.0 - features.spread]));
+    scores.insert("yeah".to_string(), with(pattern([false, true, true, false, false]), &[features.spread]));
+    scores.insert("three".to_string(), combine(&pattern([false, true, true, true, false])));
+    scores.insert("gun".to_string(), combine(&pattern([true, true, false, false, false])));
+    scores.insert("six".to_string(), combine(&pattern([true, false, false, false, true])));
+    scores.insert("thumbup".to_string(), with(pattern([true, false, false, false, false]), &[features.thumb_up]));
+
+    // 爱心（比心）：拇指与食指指尖相触，食指半伸出，其余三指弯曲
+    let curled_rest: Vec<f64> = features.extension[2..].iter().map(|e| 1.0 - e).collect();
+    scores.insert("love".to_string(), with(curled_rest, &[features.pinch, features.index_reach]));
+
+    scores
+}
+
+// 对21个关键点进行分类，结果确定且不依赖前端
+pub fn classify_hand(lm: &[Landmark]) -> Result<GestureClassification, String> {
+    let features = extract_hand_features(lm)?;
+    let mut scores = score_gestures(&features);
+    // 自定义手势与内置手势一起参与比较
+    scores.extend(score_custom_gestures(lm));
+
+    Ok(classify_scores(scores))
+}
+
+// 取得分最高的手势，低于最低置信度时为unknown
+fn classify_scores(scores: BTreeMap<String, f64>) -> GestureClassification {
+    let (gesture_type, confidence) = scores
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(gesture, score)| (gesture.clone(), *score))
+        .unwrap_or_else(|| ("unknown".to_string(), 0.0));
+
+    let gesture_type = if confidence < MIN_CLASSIFY_CONFIDENCE {
+        "unknown".to_string()
+    } else {
+        gesture_type
+    };
+
+    GestureClassification {
+        gesture_type,
+        confidence,
+        scores,
+    }
+}
+
 // Tauri命令：获取手势配置
 #[tauri::command]
 pub async fn get_gesture_configs() -> Result<Vec<GestureConfig>, String> {
@@ -172,7 +481,7 @@ pub async fn get_gesture_configs() -> Result<Vec<GestureConfig>, String> {
     };
     let pool = db.get_pool();
     let configs = sqlx::query_as::<_, GestureConfig>(
-        "SELECT id, name, gesture_type, threshold, enabled, created_at, updated_at FROM gesture_configs ORDER BY id"
+        "SELECT id, name, gesture_type, threshold, enabled, custom, kind, created_at, updated_at FROM gesture_configs ORDER BY id"
     )
     .fetch_all(pool)
     .await
@@ -206,48 +515,429 @@ pub async fn update_gesture_config(
     Ok(result.rows_affected() > 0)
 }
 
-// Tauri命令：保存手势记录
+// 自定义手势类型名只允许小写字母、数字、下划线和连字符
+fn validate_gesture_type(gesture_type: &str) -> Result<(), String> {
+    let valid_chars = gesture_type
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if gesture_type.is_empty() || gesture_type.len() > 20 || !valid_chars {
+        return Err("手势类型需为1~20位小写字母、数字、下划线或连字符".to_string());
+    }
+    if gesture_type == "unknown" {
+        return Err("unknown为保留的手势类型".to_string());
+    }
+    Ok(())
+}
+
+// Tauri命令：创建自定义手势配置，创建后通过录入样本使其可被识别
 #[tauri::command]
-pub async fn save_gesture_record(
+pub async fn create_gesture_config(
+    name: String,
     gesture_type: String,
-    confidence: f64,
-    keypoints: Option<String>,
-    frame_width: Option<i32>,
-    frame_height: Option<i32>,
-) -> Result<i64, String> {
+    threshold: Option<f64>,
+) -> Result<GestureConfig, String> {
     use crate::DATABASE;
     let db = {
         let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
         db_guard.as_ref().ok_or("数据库未初始化")?.clone()
     };
     let pool = db.get_pool();
-    // 插入手势记录
-    let record_result = sqlx::query(
-        "INSERT INTO gesture_records (gesture_type, confidence) VALUES (?, ?) RETURNING id"
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("手势名称不能为空".to_string());
+    }
+    validate_gesture_type(&gesture_type)?;
+    let threshold = threshold.unwrap_or(0.8);
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err("阈值需在0~1之间".to_string());
+    }
+
+    let exists = sqlx::query("SELECT COUNT(*) as count FROM gesture_configs WHERE gesture_type = ?")
+        .bind(&gesture_type)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .get::<i64, _>("count") > 0;
+    if exists {
+        return Err(format!("手势类型{}已存在", gesture_type));
+    }
+
+    let config = sqlx::query_as::<_, GestureConfig>(
+        r#"
+        INSERT INTO gesture_configs (name, gesture_type, threshold, custom) VALUES (?, ?, ?, true)
+        RETURNING id, name, gesture_type, threshold, enabled, custom, kind, created_at, updated_at
+        "#,
     )
+    .bind(name)
     .bind(&gesture_type)
-    .bind(confidence)
+    .bind(threshold)
     .fetch_one(pool)
     .await
     .map_err(|e| e.to_string())?;
 
-    let record_id: i64 = record_result.get("id");
+    Ok(config)
+}
+
+// Tauri命令：删除自定义手势配置及其模板和绑定，内置手势不可删除
+#[tauri::command]
+pub async fn delete_gesture_config(id: i64) -> Result<bool, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let Some(row) = sqlx::query("SELECT gesture_type, custom FROM gesture_configs WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(false);
+    };
+    if !row.get::<bool, _>("custom") {
+        return Err("内置手势不可删除".to_string());
+    }
+    let gesture_type: String = row.get("gesture_type");
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    delete_config_templates(&mut tx, id).await.map_err(|e| e.to_string())?;
+
+    // 动作触发日志保留，只解除与绑定的关联
+    sqlx::query("UPDATE gesture_action_logs SET binding_id = NULL WHERE binding_id IN (SELECT id FROM gesture_bindings WHERE gesture_type = ?)")
+        .bind(&gesture_type)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    sqlx::query("DELETE FROM gesture_bindings WHERE gesture_type = ?")
+        .bind(&gesture_type)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 历史记录保留，只解除与配置的关联
+    sqlx::query("UPDATE gesture_records SET config_id = NULL WHERE config_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let result = sqlx::query("DELETE FROM gesture_configs WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    reload_template_cache(pool).await.map_err(|e| e.to_string())?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// 读取已启用手势的阈值
+async fn load_enabled_thresholds(pool: &SqlitePool) -> Result<HashMap<String, f64>, sqlx::Error> {
+    let rows = sqlx::query("SELECT gesture_type, threshold FROM gesture_configs WHERE enabled = true")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("gesture_type"), row.get("threshold")))
+        .collect())
+}
+
+// 插入手势记录，有关键点数据时一并保存
+async fn insert_gesture_record(
+    pool: &SqlitePool,
+    gesture_type: &str,
+    confidence: f64,
+    keypoints: Option<(Vec<Landmark>, i32, i32)>,
+) -> Result<i64, sqlx::Error> {
+    // 同时记录当时生效的配置及阈值，之后修改阈值不影响历史记录的统计
+    let record_id: i64 = sqlx::query(
+        r#"
+        INSERT INTO gesture_records (gesture_type, confidence, config_id, threshold)
+        VALUES (?, ?, (SELECT id FROM gesture_configs WHERE gesture_type = ?), (SELECT threshold FROM gesture_configs WHERE gesture_type = ?))
+        RETURNING id
+        "#,
+    )
+    .bind(gesture_type)
+    .bind(confidence)
+    .bind(gesture_type)
+    .bind(gesture_type)
+    .fetch_one(pool)
+    .await?
+    .get("id");
 
-    // 如果有关键点数据，也保存
-    if let (Some(kp), Some(fw), Some(fh)) = (keypoints, frame_width, frame_height) {
+    if let Some((landmarks, fw, fh)) = keypoints {
         sqlx::query(
             "INSERT INTO keypoint_data (record_id, keypoints, frame_width, frame_height) VALUES (?, ?, ?, ?)"
         )
         .bind(record_id)
-        .bind(kp)
+        .bind(encode_landmarks(&landmarks))
         .bind(fw)
         .bind(fh)
         .execute(pool)
+        .await?;
+    }
+
+    Ok(record_id)
+}
+
+// 持久化并推送稳定手势事件，返回新开始手势的记录ID
+async fn apply_smoothed_events(
+    app: &tauri::AppHandle,
+    pool: &SqlitePool,
+    session_id: &str,
+    events: Vec<SmoothedGestureEvent>,
+    mut record_id: Option<i64>,
+    mut keypoints: Option<(Vec<Landmark>, i32, i32)>,
+) -> Result<Option<i64>, String> {
+    let initial_record_id = record_id;
+    let mut started = None;
+
+    for event in events {
+        let (event_name, payload_record_id) = match &event {
+            SmoothedGestureEvent::Start { gesture_type, confidence } => {
+                let id = insert_gesture_record(pool, gesture_type, *confidence, keypoints.take())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                record_id = Some(id);
+                started = Some(id);
+
+                // 只有稳定开始的手势才触发绑定动作
+                if let Err(e) = crate::gesture_actions::fire_gesture_bindings(app, pool, gesture_type, *confidence, Some(id)).await {
+                    eprintln!("触发手势动作失败: {}", e);
+                }
+                (GESTURE_START_EVENT, Some(id))
+            }
+            SmoothedGestureEvent::End { duration_ms, .. } => {
+                let id = record_id.take();
+                if let Some(id) = id {
+                    sqlx::query(
+                        "UPDATE gesture_records SET ended_at = CURRENT_TIMESTAMP, duration_ms = ? WHERE id = ?"
+                    )
+                    .bind(duration_ms)
+                    .bind(id)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                }
+                (GESTURE_END_EVENT, id)
+            }
+        };
+
+        let payload = GestureEventPayload {
+            session_id: session_id.to_string(),
+            record_id: payload_record_id,
+            event,
+        };
+        let _ = app.emit(event_name, &payload);
+    }
+
+    // 期间其他帧已更新记录ID时不覆盖
+    if let Ok(mut sessions) = GESTURE_SESSIONS.lock() {
+        if let Some(smoother) = sessions.get_mut(session_id) {
+            if smoother.active_record_id == initial_record_id {
+                smoother.active_record_id = record_id;
+            }
+        }
+    }
+
+    Ok(started)
+}
+
+// Tauri命令：提交一帧手势检测结果
+// 逐帧结果先经过会话内的平滑状态机，只有稳定的手势开始/结束才会保存和推送，
+// 返回值为本帧新开始的手势记录ID；关键点不合法时整帧拒绝
+#[tauri::command]
+pub async fn save_gesture_record(
+    app: tauri::AppHandle,
+    gesture_type: String,
+    confidence: f64,
+    keypoints: Option<String>,
+    frame_width: Option<i32>,
+    frame_height: Option<i32>,
+    session_id: Option<String>,
+) -> Result<Option<i64>, GestureRecordError> {
+    let keypoints = keypoints
+        .map(|payload| parse_keypoint_payload(&payload, frame_width, frame_height))
+        .transpose()?;
+
+    if !GESTURE_RECOGNITION_ENABLED.load(Ordering::Relaxed) {
+        return Ok(None);
+    }
+
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let thresholds = load_enabled_thresholds(pool).await.map_err(|e| e.to_string())?;
+    let session_id = session_id.unwrap_or_else(|| "default".to_string());
+
+    let now = Instant::now();
+
+    // 有关键点时同时送入运动轨迹，识别动态手势
+    let motion = match &keypoints {
+        Some((landmarks, _, _)) => {
+            let mut sessions = MOTION_SESSIONS.lock().map_err(|e| e.to_string())?;
+            sessions.entry(session_id.clone()).or_default().push(landmarks.clone(), now)
+        }
+        None => None,
+    };
+    if let Some(detection) = motion {
+        record_motion_gesture(&app, pool, &session_id, detection, &thresholds).await?;
+    }
+
+    let (events, record_id) = {
+        let mut sessions = GESTURE_SESSIONS.lock().map_err(|e| e.to_string())?;
+        let smoother = sessions.entry(session_id.clone()).or_default();
+        let events = smoother.push(&gesture_type, confidence, |g| thresholds.get(g).copied(), now);
+        (events, smoother.active_record_id)
+    };
+
+    if events.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(apply_smoothed_events(&app, pool, &session_id, events, record_id, keypoints).await?)
+}
+
+// 保存并推送一次动态手势，未启用或低于阈值时忽略
+async fn record_motion_gesture(
+    app: &tauri::AppHandle,
+    pool: &SqlitePool,
+    session_id: &str,
+    detection: MotionDetection,
+    thresholds: &HashMap<String, f64>,
+) -> Result<(), String> {
+    let MotionDetection { classification, duration_ms } = detection;
+    let Some(threshold) = thresholds.get(&classification.gesture_type) else { return Ok(()) };
+    if classification.confidence < *threshold {
+        return Ok(());
+    }
+
+    let record_id = insert_gesture_record(pool, &classification.gesture_type, classification.confidence, None)
         .await
         .map_err(|e| e.to_string())?;
+    sqlx::query("UPDATE gesture_records SET ended_at = CURRENT_TIMESTAMP, duration_ms = ? WHERE id = ?")
+        .bind(duration_ms)
+        .bind(record_id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Err(e) = crate::gesture_actions::fire_gesture_bindings(
+        app,
+        pool,
+        &classification.gesture_type,
+        classification.confidence,
+        Some(record_id),
+    )
+    .await
+    {
+        eprintln!("触发手势动作失败: {}", e);
     }
 
-    Ok(record_id)
+    let payload = MotionGestureEventPayload {
+        session_id: session_id.to_string(),
+        record_id,
+        gesture_type: classification.gesture_type,
+        confidence: classification.confidence,
+        duration_ms,
+    };
+    let _ = app.emit(MOTION_GESTURE_EVENT, &payload);
+
+    Ok(())
+}
+
+// Tauri命令：结束识别会话，当前保持中的手势立即结束
+#[tauri::command]
+pub async fn end_gesture_session(app: tauri::AppHandle, session_id: Option<String>) -> Result<bool, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let session_id = session_id.unwrap_or_else(|| "default".to_string());
+
+    let motion = MOTION_SESSIONS.lock().map_err(|e| e.to_string())?.remove(&session_id);
+    if let Some(detection) = motion.and_then(|mut tracker| tracker.flush()) {
+        let thresholds = load_enabled_thresholds(pool).await.map_err(|e| e.to_string())?;
+        record_motion_gesture(&app, pool, &session_id, detection, &thresholds).await?;
+    }
+
+    let Some(mut smoother) = GESTURE_SESSIONS.lock().map_err(|e| e.to_string())?.remove(&session_id) else {
+        return Ok(false);
+    };
+    let events = smoother.flush(Instant::now());
+    apply_smoothed_events(&app, pool, &session_id, events, smoother.active_record_id, None).await?;
+
+    Ok(true)
+}
+
+// 检查识别会话的间隔
+const SESSION_SWEEP_INTERVAL: Duration = Duration::from_millis(100);
+
+// 定期结束手已离开画面的手势，并移除长时间没有新帧的会话
+pub fn start_gesture_session_sweeper(app: tauri::AppHandle, db: crate::services::DatabaseService) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SESSION_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = sweep_gesture_sessions(&app, db.get_pool()).await {
+                eprintln!("检查手势会话失败: {}", e);
+            }
+        }
+    });
+}
+
+async fn sweep_gesture_sessions(app: &tauri::AppHandle, pool: &SqlitePool) -> Result<(), String> {
+    let now = Instant::now();
+
+    let motions: Vec<(String, MotionDetection)> = {
+        let mut sessions = MOTION_SESSIONS.lock().map_err(|e| e.to_string())?;
+        let idle: Vec<String> = sessions
+            .iter()
+            .filter(|(_, tracker)| tracker.is_idle(now, SESSION_IDLE_TIMEOUT))
+            .map(|(id, _)| id.clone())
+            .collect();
+        idle.into_iter()
+            .filter_map(|id| {
+                let detection = sessions.remove(&id)?.flush()?;
+                Some((id, detection))
+            })
+            .collect()
+    };
+    if !motions.is_empty() {
+        let thresholds = load_enabled_thresholds(pool).await.map_err(|e| e.to_string())?;
+        for (session_id, detection) in motions {
+            record_motion_gesture(app, pool, &session_id, detection, &thresholds).await?;
+        }
+    }
+
+    let ended: Vec<(String, Vec<SmoothedGestureEvent>, Option<i64>)> = {
+        let mut sessions = GESTURE_SESSIONS.lock().map_err(|e| e.to_string())?;
+        let ended = sessions
+            .iter_mut()
+            .filter_map(|(id, smoother)| {
+                let events = smoother.tick(now);
+                (!events.is_empty()).then(|| (id.clone(), events, smoother.active_record_id.take()))
+            })
+            .collect();
+        sessions.retain(|_, smoother| !smoother.is_idle(now, SESSION_IDLE_TIMEOUT));
+        ended
+    };
+    for (session_id, events, record_id) in ended {
+        apply_smoothed_events(app, pool, &session_id, events, record_id, None).await?;
+    }
+
+    Ok(())
 }
 
 // Tauri命令：获取手势历史记录
@@ -266,7 +956,7 @@ pub async fn get_gesture_history(
     let offset = offset.unwrap_or(0);
 
     let records = sqlx::query_as::<_, GestureRecord>(
-        "SELECT id, gesture_type, confidence, detected_at, config_id FROM gesture_records ORDER BY detected_at DESC LIMIT ? OFFSET ?"
+        "SELECT * FROM gesture_records ORDER BY detected_at DESC LIMIT ? OFFSET ?"
     )
     .bind(limit)
     .bind(offset)
@@ -277,29 +967,181 @@ pub async fn get_gesture_history(
     Ok(records)
 }
 
-// Tauri命令：获取手势统计
+// Tauri命令：将记录标记为识别错误并保存正确的手势，correct_type为空时撤销纠正
+// 纠正结果会用于混淆矩阵、阈值校准和数据集导出
+#[tauri::command]
+pub async fn correct_gesture_record(record_id: i64, correct_type: Option<String>) -> Result<bool, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+
+    if let Some(correct_type) = correct_type.as_deref().filter(|t| *t != "unknown") {
+        let exists = sqlx::query("SELECT COUNT(*) as count FROM gesture_configs WHERE gesture_type = ?")
+            .bind(correct_type)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .get::<i64, _>("count") > 0;
+        if !exists {
+            return Err(format!("未知的手势类型: {}", correct_type));
+        }
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE gesture_records
+        SET corrected_type = ?, corrected_at = CASE WHEN ? IS NULL THEN NULL ELSE CURRENT_TIMESTAMP END
+        WHERE id = ?
+        "#,
+    )
+    .bind(&correct_type)
+    .bind(&correct_type)
+    .bind(record_id)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// 将前端传入的时间统一为数据库中的 "YYYY-MM-DD HH:MM:SS"（UTC），便于与CURRENT_TIMESTAMP比较
+pub(crate) fn normalize_sql_timestamp(value: &str) -> String {
+    match chrono::DateTime::parse_from_rfc3339(value) {
+        Ok(time) => time.with_timezone(&chrono::Utc).format("%Y-%m-%d %H:%M:%S").to_string(),
+        Err(_) => value.to_string(),
+    }
+}
+
+fn keypoint_data_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<KeypointData, String> {
+    let record_id: i64 = row.get("record_id");
+    let keypoints: Vec<u8> = row.get("keypoints");
+    let keypoints = decode_landmarks(&keypoints)
+        .map_err(|e| format!("记录{}: {}", record_id, e))?;
+
+    Ok(KeypointData {
+        id: Some(row.get("id")),
+        record_id,
+        keypoints,
+        frame_width: row.get("frame_width"),
+        frame_height: row.get("frame_height"),
+        created_at: row.get("created_at"),
+    })
+}
+
+// Tauri命令：获取单条手势记录的关键点数据
+#[tauri::command]
+pub async fn get_record_keypoints(record_id: i64) -> Result<Vec<KeypointData>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let rows = sqlx::query(
+        "SELECT id, record_id, keypoints, frame_width, frame_height, created_at FROM keypoint_data WHERE record_id = ? ORDER BY id"
+    )
+    .bind(record_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    rows.iter().map(keypoint_data_from_row).collect()
+}
+
+// Tauri命令：按时间范围获取带关键点的手势记录，按时间先后排列便于回放
 #[tauri::command]
-pub async fn get_gesture_stats() -> Result<GestureStats, String> {
+pub async fn get_keypoint_records(
+    start: Option<String>,
+    end: Option<String>,
+    limit: Option<i32>,
+) -> Result<Vec<GestureRecordKeypoints>, String> {
     use crate::DATABASE;
     let db = {
         let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
         db_guard.as_ref().ok_or("数据库未初始化")?.clone()
     };
     let pool = db.get_pool();
+    let start = start.as_deref().map(normalize_sql_timestamp);
+    let end = end.as_deref().map(normalize_sql_timestamp);
+
+    let records = sqlx::query_as::<_, GestureRecord>(
+        r#"
+        SELECT r.*
+        FROM gesture_records r
+        WHERE EXISTS (SELECT 1 FROM keypoint_data k WHERE k.record_id = r.id)
+          AND (? IS NULL OR detected_at >= ?)
+          AND (? IS NULL OR detected_at <= ?)
+        ORDER BY detected_at, id
+        LIMIT ?
+        "#,
+    )
+    .bind(&start)
+    .bind(&start)
+    .bind(&end)
+    .bind(&end)
+    .bind(limit.unwrap_or(200))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut result = Vec::with_capacity(records.len());
+    for record in records {
+        let rows = sqlx::query(
+            "SELECT id, record_id, keypoints, frame_width, frame_height, created_at FROM keypoint_data WHERE record_id = ? ORDER BY id"
+        )
+        .bind(record.id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        // 单条损坏的数据不影响整段回放
+        let keypoints = rows
+            .iter()
+            .filter_map(|row| match keypoint_data_from_row(row) {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    None
+                }
+            })
+            .collect();
+        result.push(GestureRecordKeypoints { record, keypoints });
+    }
+
+    Ok(result)
+}
+
+// Tauri命令：获取手势统计，可按时间范围过滤
+#[tauri::command]
+pub async fn get_gesture_stats(start: Option<String>, end: Option<String>) -> Result<GestureStats, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let range = DateRange::new(start, end);
+
     // 总检测次数
-    let total_detections: i64 = sqlx::query("SELECT COUNT(*) as count FROM gesture_records")
+    let sql = format!("SELECT COUNT(*) as count FROM gesture_records WHERE {}", RANGE_FILTER);
+    let total_detections: i64 = range.bind(sqlx::query(&sql))
         .fetch_one(pool)
         .await
         .map_err(|e| e.to_string())?
         .get("count");
 
     // 各手势统计
-    let gesture_counts_rows = sqlx::query(
-        "SELECT gesture_type, COUNT(*) as count FROM gesture_records GROUP BY gesture_type"
-    )
-    .fetch_all(pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    let sql = format!(
+        "SELECT gesture_type, COUNT(*) as count FROM gesture_records WHERE {} GROUP BY gesture_type",
+        RANGE_FILTER
+    );
+    let gesture_counts_rows = range.bind(sqlx::query(&sql))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
 
     let mut gesture_counts = HashMap::new();
     for row in gesture_counts_rows {
@@ -309,7 +1151,8 @@ pub async fn get_gesture_stats() -> Result<GestureStats, String> {
     }
 
     // 平均置信度
-    let average_confidence: f64 = sqlx::query("SELECT AVG(confidence) as avg_conf FROM gesture_records")
+    let sql = format!("SELECT AVG(confidence) as avg_conf FROM gesture_records WHERE {}", RANGE_FILTER);
+    let average_confidence: f64 = range.bind(sqlx::query(&sql))
         .fetch_one(pool)
         .await
         .map_err(|e| e.to_string())?
@@ -317,13 +1160,15 @@ pub async fn get_gesture_stats() -> Result<GestureStats, String> {
         .unwrap_or(0.0);
 
     // 最后检测时间
-    let last_detection: Option<String> = sqlx::query(
-        "SELECT detected_at FROM gesture_records ORDER BY detected_at DESC LIMIT 1"
-    )
-    .fetch_optional(pool)
-    .await
-    .map_err(|e| e.to_string())?
-    .map(|row| row.get("detected_at"));
+    let sql = format!(
+        "SELECT detected_at FROM gesture_records WHERE {} ORDER BY detected_at DESC LIMIT 1",
+        RANGE_FILTER
+    );
+    let last_detection: Option<String> = range.bind(sqlx::query(&sql))
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|row| row.get("detected_at"));
 
     Ok(GestureStats {
         total_detections,
@@ -342,19 +1187,25 @@ pub async fn clear_gesture_history() -> Result<bool, String> {
         db_guard.as_ref().ok_or("数据库未初始化")?.clone()
     };
     let pool = db.get_pool();
-    // 先删除关键点数据
-    sqlx::query("DELETE FROM keypoint_data")
-        .execute(pool)
+    // 与保留策略共用删除逻辑，在同一事务中置空模板和动作日志的引用后再删除
+    let ids: Vec<i64> = sqlx::query("SELECT id FROM gesture_records")
+        .fetch_all(pool)
         .await
-        .map_err(|e| e.to_string())?;
-
-    // 再删除手势记录
-    let result = sqlx::query("DELETE FROM gesture_records")
-        .execute(pool)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|row| row.get("id"))
+        .collect();
+    let result = delete_records_by_ids(pool, &ids)
         .await
         .map_err(|e| e.to_string())?;
 
-    Ok(result.rows_affected() > 0)
+    Ok(result.records > 0)
+}
+
+// Tauri命令：根据MediaPipe的21个关键点识别手势
+#[tauri::command]
+pub async fn classify_keypoints(keypoints: Vec<Landmark>) -> Result<GestureClassification, String> {
+    classify_hand(&keypoints)
 }
 
 // 实现 sqlx::FromRow for GestureConfig
@@ -366,6 +1217,8 @@ impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for GestureConfig {
             gesture_type: row.get("gesture_type"),
             threshold: row.get("threshold"),
             enabled: row.get("enabled"),
+            custom: row.get("custom"),
+            kind: row.get("kind"),
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
         })
@@ -381,6 +1234,161 @@ impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for GestureRecord {
             confidence: row.get("confidence"),
             detected_at: row.get("detected_at"),
             config_id: row.get("config_id"),
+            threshold: row.get("threshold"),
+            ended_at: row.get("ended_at"),
+            duration_ms: row.get("duration_ms"),
+            corrected_type: row.get("corrected_type"),
+            corrected_at: row.get("corrected_at"),
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 四指根部相对手腕的位置，手掌大小（手腕到中指根部）为0.2
+    const FINGER_MCPS: [(f64, f64); 4] = [(-0.06, -0.19), (0.0, -0.2), (0.05, -0.19), (0.1, -0.17)];
+    // 拇指伸出方向
+    const THUMB_OUT: [f64; 3] = [-1.0, -0.5, 0.0];
+    const THUMB_UP: [f64; 3] = [-0.9, -1.0, 0.0];
+
+    #[derive(Clone, Copy)]
+    enum Finger {
+        Extended(f64), // 与竖直向上的夹角（度）
+        Curled,
+    }
+    use Finger::{Curled, Extended};
+
+    fn point(x: f64, y: f64, z: f64) -> Landmark {
+        Landmark { x, y, z }
+    }
+
+    fn offset(p: Landmark, d: [f64; 3], len: f64) -> Landmark {
+        let norm = (d[0].powi(2) + d[1].powi(2) + d[2].powi(2)).sqrt();
+        point(p.x + d[0] / norm * len, p.y + d[1] / norm * len, p.z + d[2] / norm * len)
+    }
+
+    fn upward(angle: f64) -> [f64; 3] {
+        let angle = angle.to_radians();
+        [angle.sin(), -angle.cos(), 0.0]
+    }
+
+    // 构造手心朝向镜头、手指朝上的21个关键点；thumb为空时拇指弯向掌心
+    fn hand(thumb: Option<[f64; 3]>, fingers: [Finger; 4]) -> Vec<Landmark> {
+        let wrist = point(0.5, 0.8, 0.0);
+        let cmc = offset(wrist, [-0.05, -0.04, 0.0], 0.064);
+        let mcp = offset(cmc, thumb.unwrap_or([-0.6, -0.8, 0.0]), 0.05);
+        let (ip, tip) = match thumb {
+            Some(d) => {
+                let ip = offset(mcp, d, 0.04);
+                (ip, offset(ip, d, 0.035))
+            }
+            None => {
+                let ip = offset(mcp, [0.6, -0.8, -0.3], 0.04);
+                (ip, offset(ip, [0.5, -0.3, -0.6], 0.03))
+            }
+        };
+        let mut lm = vec![wrist, cmc, mcp, ip, tip];
+
+        for ((x, y), finger) in FINGER_MCPS.into_iter().zip(fingers) {
+            let base = point(wrist.x + x, wrist.y + y, 0.0);
+            // 弯曲的手指先向上，再折向镜头，最后指尖回指手腕
+            let dirs = match finger {
+                Extended(angle) => [upward(angle); 3],
+                Curled => [[0.0, -1.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]],
+            };
+            let pip = offset(base, dirs[0], 0.05);
+            let dip = offset(pip, dirs[1], 0.04);
+            lm.extend([base, pip, dip, offset(dip, dirs[2], 0.03)]);
+        }
+        lm
+    }
+
+    // 比心：食指半弯，拇指指尖贴住食指指尖，其余三指弯曲
+    fn love() -> Vec<Landmark> {
+        let mut lm = hand(None, [Curled; 4]);
+        let pip = offset(lm[INDEX_MCP], [0.0, -1.0, 0.0], 0.05);
+        let dip = offset(pip, [0.0, -1.0, -1.0], 0.04);
+        let tip = offset(dip, [0.0, 0.0, -1.0], 0.03);
+        lm[6] = pip;
+        lm[7] = dip;
+        lm[INDEX_TIP] = tip;
+        lm[THUMB_IP] = point((lm[THUMB_MCP].x + tip.x) / 2.0 - 0.01, (lm[THUMB_MCP].y + tip.y) / 2.0, tip.z / 2.0);
+        lm[THUMB_TIP] = point(tip.x - 0.01, tip.y + 0.01, tip.z);
+        lm
+    }
+
+    fn seeded_poses() -> Vec<(&'static str, Vec<Landmark>)> {
+        vec![
+            ("fist", hand(None, [Curled; 4])),
+            ("five", hand(Some(THUMB_OUT), [Extended(-10.0), Extended(0.0), Extended(8.0), Extended(16.0)])),
+            ("one", hand(None, [Extended(0.0), Curled, Curled, Curled])),
+            ("two", hand(None, [Extended(0.0), Extended(0.0), Curled, Curled])),
+            ("yeah", hand(None, [Extended(-15.0), Extended(15.0), Curled, Curled])),
+            ("three", hand(None, [Extended(-5.0), Extended(0.0), Extended(5.0), Curled])),
+            ("gun", hand(Some(THUMB_OUT), [Extended(0.0), Curled, Curled, Curled])),
+            ("six", hand(Some(THUMB_OUT), [Curled, Curled, Curled, Extended(20.0)])),
+            ("thumbup", hand(Some(THUMB_UP), [Curled; 4])),
+            ("love", love()),
+        ]
+    }
+
+    // 只使用内置手势评分，不受已录入的自定义模板影响
+    fn classify_builtin(lm: &[Landmark]) -> GestureClassification {
+        classify_scores(score_gestures(&extract_hand_features(lm).unwrap()))
+    }
+
+    #[test]
+    fn classifies_seeded_static_gestures() {
+        for (expected, lm) in seeded_poses() {
+            let result = classify_builtin(&lm);
+            assert_eq!(result.gesture_type, expected, "scores: {:?}", result.scores);
+            assert!(result.confidence >= MIN_CLASSIFY_CONFIDENCE);
+        }
+    }
+
+    #[test]
+    fn classification_is_deterministic() {
+        for (_, lm) in seeded_poses() {
+            let first = classify_builtin(&lm);
+            let second = classify_builtin(&lm);
+            assert_eq!(first.gesture_type, second.gesture_type);
+            assert_eq!(first.scores, second.scores);
+        }
+    }
+
+    #[test]
+    fn distinguishes_two_from_yeah_by_spread() {
+        let two = extract_hand_features(&hand(None, [Extended(0.0), Extended(0.0), Curled, Curled])).unwrap();
+        let yeah = extract_hand_features(&hand(None, [Extended(-15.0), Extended(15.0), Curled, Curled])).unwrap();
+        assert!(two.spread < 0.1);
+        assert!(yeah.spread > 0.9);
+    }
+
+    #[test]
+    fn rejects_wrong_landmark_count() {
+        let lm = hand(None, [Curled; 4]);
+        for count in [0, HAND_LANDMARK_COUNT - 1, HAND_LANDMARK_COUNT + 1] {
+            let landmarks: Vec<Landmark> = lm.iter().cycle().take(count).copied().collect();
+            let expected = format!("需要{}个关键点，实际为{}", HAND_LANDMARK_COUNT, count);
+            assert_eq!(extract_hand_features(&landmarks).unwrap_err(), expected);
+            assert_eq!(classify_hand(&landmarks).unwrap_err(), expected);
+        }
+    }
+
+    #[test]
+    fn low_scores_are_unknown() {
+        let scores = BTreeMap::from([("fist".to_string(), 0.3), ("five".to_string(), 0.45)]);
+        let result = classify_scores(scores);
+        assert_eq!(result.gesture_type, "unknown");
+        assert_eq!(result.confidence, 0.45);
+        assert_eq!(classify_scores(BTreeMap::new()).gesture_type, "unknown");
+    }
+
+    #[test]
+    fn rejects_degenerate_hand() {
+        let lm = vec![point(0.5, 0.5, 0.0); HAND_LANDMARK_COUNT];
+        assert_eq!(classify_hand(&lm).unwrap_err(), "关键点数据无效");
+    }
+}
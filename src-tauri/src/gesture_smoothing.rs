@@ -0,0 +1,373 @@
+// 手势时序平滑 - 将逐帧检测结果转换为稳定的手势开始/结束事件
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const GESTURE_START_EVENT: &str = "gesture://start";
+pub const GESTURE_END_EVENT: &str = "gesture://end";
+
+// 每个识别会话（摄像头页面、移动端等）独立的状态机
+pub static GESTURE_SESSIONS: Lazy<Mutex<HashMap<String, GestureSmoother>>> = Lazy::new(|| {
+    Mutex::new(HashMap::new())
+});
+
+// 超过该时长没有新帧的会话会被移除
+pub const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// 平滑参数
+#[derive(Debug, Clone, Copy)]
+pub struct SmoothingParams {
+    // 滑动窗口时长
+    pub window: Duration,
+    // 窗口内获胜手势需要的最低票数占比
+    pub min_vote_ratio: f64,
+    // 窗口内至少需要的帧数
+    pub min_frames: usize,
+    // 手势需要持续多久才算开始
+    pub min_hold: Duration,
+    // 手势消失多久才算结束
+    pub release: Duration,
+    // 阈值两侧的滞回宽度：开始时需高于 threshold + hysteresis，保持只需高于 threshold - hysteresis
+    pub hysteresis: f64,
+}
+
+// 默认滞回宽度，校准推荐的阈值也需据此留出开始所需的余量
+pub const DEFAULT_HYSTERESIS: f64 = 0.05;
+
+impl Default for SmoothingParams {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(500),
+            min_vote_ratio: 0.6,
+            min_frames: 3,
+            min_hold: Duration::from_millis(300),
+            release: Duration::from_millis(400),
+            hysteresis: DEFAULT_HYSTERESIS,
+        }
+    }
+}
+
+// 稳定手势事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SmoothedGestureEvent {
+    Start {
+        gesture_type: String,
+        confidence: f64,
+    },
+    End {
+        gesture_type: String,
+        confidence: f64,
+        duration_ms: i64,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Frame {
+    gesture_type: String,
+    confidence: f64,
+    at: Instant,
+}
+
+#[derive(Debug, Clone)]
+struct ActiveGesture {
+    gesture_type: String,
+    started_at: Instant,
+    last_seen: Instant,
+    confidence_sum: f64,
+    frames: u32,
+}
+
+impl ActiveGesture {
+    fn average_confidence(&self) -> f64 {
+        self.confidence_sum / self.frames.max(1) as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GestureSmoother {
+    params: SmoothingParams,
+    window: VecDeque<Frame>,
+    candidate: Option<(String, Instant)>,
+    active: Option<ActiveGesture>,
+    last_frame: Option<Instant>,
+    // 当前手势对应的记录ID，结束时用于回填
+    pub active_record_id: Option<i64>,
+}
+
+impl Default for GestureSmoother {
+    fn default() -> Self {
+        Self::new(SmoothingParams::default())
+    }
+}
+
+impl GestureSmoother {
+    pub fn new(params: SmoothingParams) -> Self {
+        Self {
+            params,
+            window: VecDeque::new(),
+            candidate: None,
+            active: None,
+            last_frame: None,
+            active_record_id: None,
+        }
+    }
+
+    // 输入一帧检测结果，threshold_of返回手势的配置阈值（未启用的手势返回None）
+    pub fn push<F>(&mut self, gesture_type: &str, confidence: f64, threshold_of: F, now: Instant) -> Vec<SmoothedGestureEvent>
+    where
+        F: Fn(&str) -> Option<f64>,
+    {
+        let mut events = Vec::new();
+        self.last_frame = Some(now);
+
+        self.window.push_back(Frame {
+            gesture_type: gesture_type.to_string(),
+            confidence,
+            at: now,
+        });
+        while let Some(front) = self.window.front() {
+            if now.duration_since(front.at) > self.params.window {
+                self.window.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let hysteresis = self.params.hysteresis;
+
+        // 已有手势：低于下限的帧不算支持，持续缺席超过release即结束
+        if let Some(active) = self.active.as_mut() {
+            let supported = gesture_type == active.gesture_type
+                && threshold_of(gesture_type).is_some_and(|threshold| confidence >= threshold - hysteresis);
+            if supported {
+                active.last_seen = now;
+                active.confidence_sum += confidence;
+                active.frames += 1;
+            } else if now.duration_since(active.last_seen) >= self.params.release {
+                events.extend(self.finish(active_end_time(self.active.as_ref(), now)));
+            }
+        }
+
+        if self.active.is_some() {
+            return events;
+        }
+
+        // 没有手势时：窗口内多数投票，且需高于上限并保持min_hold才算开始
+        match self.vote(&threshold_of) {
+            Some(winner) => {
+                let since = match &self.candidate {
+                    Some((candidate, since)) if *candidate == winner => *since,
+                    _ => now,
+                };
+                self.candidate = Some((winner.clone(), since));
+
+                if now.duration_since(since) >= self.params.min_hold {
+                    let (confidence_sum, frames) = self.window
+                        .iter()
+                        .filter(|frame| frame.gesture_type == winner)
+                        .fold((0.0, 0u32), |(sum, count), frame| (sum + frame.confidence, count + 1));
+                    let active = ActiveGesture {
+                        gesture_type: winner.clone(),
+                        started_at: since,
+                        last_seen: now,
+                        confidence_sum,
+                        frames,
+                    };
+                    events.push(SmoothedGestureEvent::Start {
+                        gesture_type: winner,
+                        confidence: active.average_confidence(),
+                    });
+                    self.active = Some(active);
+                    self.candidate = None;
+                }
+            }
+            None => self.candidate = None,
+        }
+
+        events
+    }
+
+    // 前端只在检测到手时发送帧，手离开画面后由定时检查结束超过release未出现的手势
+    pub fn tick(&mut self, now: Instant) -> Vec<SmoothedGestureEvent> {
+        let expired = self.active
+            .as_ref()
+            .is_some_and(|active| now.duration_since(active.last_seen) >= self.params.release);
+        if expired {
+            self.flush(now)
+        } else {
+            Vec::new()
+        }
+    }
+
+    // 没有保持中的手势且长时间没有新帧
+    pub fn is_idle(&self, now: Instant, timeout: Duration) -> bool {
+        self.active.is_none() && self.last_frame.map_or(true, |at| now.duration_since(at) >= timeout)
+    }
+
+    // 主动结束当前手势（例如会话关闭、手离开画面）
+    pub fn flush(&mut self, now: Instant) -> Vec<SmoothedGestureEvent> {
+        self.window.clear();
+        self.candidate = None;
+        let end = active_end_time(self.active.as_ref(), now);
+        self.finish(end).into_iter().collect()
+    }
+
+    fn finish(&mut self, ended_at: Instant) -> Option<SmoothedGestureEvent> {
+        let active = self.active.take()?;
+        Some(SmoothedGestureEvent::End {
+            confidence: active.average_confidence(),
+            duration_ms: ended_at.duration_since(active.started_at).as_millis() as i64,
+            gesture_type: active.gesture_type,
+        })
+    }
+
+    fn vote<F>(&self, threshold_of: &F) -> Option<String>
+    where
+        F: Fn(&str) -> Option<f64>,
+    {
+        if self.window.len() < self.params.min_frames {
+            return None;
+        }
+
+        let mut votes: HashMap<&str, usize> = HashMap::new();
+        for frame in &self.window {
+            let Some(threshold) = threshold_of(&frame.gesture_type) else { continue };
+            if frame.confidence >= threshold + self.params.hysteresis {
+                *votes.entry(frame.gesture_type.as_str()).or_insert(0) += 1;
+            }
+        }
+
+        let required = (self.window.len() as f64 * self.params.min_vote_ratio).ceil() as usize;
+        votes.into_iter()
+            .filter(|(_, count)| *count >= required)
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(gesture, _)| gesture.to_string())
+    }
+}
+
+// 手势的结束时间取最后一次被支持的时刻
+fn active_end_time(active: Option<&ActiveGesture>, now: Instant) -> Instant {
+    active.map(|active| active.last_seen).unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: f64 = 0.8;
+
+    fn threshold_of(gesture: &str) -> Option<f64> {
+        (gesture == "fist").then_some(THRESHOLD)
+    }
+
+    // 从t=from_ms起每50ms输入一帧，返回产生事件的时刻（毫秒）与事件
+    fn feed(
+        smoother: &mut GestureSmoother,
+        base: Instant,
+        from_ms: u64,
+        to_ms: u64,
+        gesture: &str,
+        confidence: f64,
+    ) -> Vec<(u64, SmoothedGestureEvent)> {
+        (from_ms..=to_ms)
+            .step_by(50)
+            .flat_map(|ms| {
+                smoother
+                    .push(gesture, confidence, threshold_of, base + Duration::from_millis(ms))
+                    .into_iter()
+                    .map(move |event| (ms, event))
+            })
+            .collect()
+    }
+
+    fn started(smoother: &mut GestureSmoother, base: Instant) {
+        let events = feed(smoother, base, 0, 400, "fist", 0.9);
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn starts_after_min_hold() {
+        let base = Instant::now();
+        let mut smoother = GestureSmoother::default();
+
+        // 第3帧（100ms）起成为候选，保持300ms后开始
+        assert!(feed(&mut smoother, base, 0, 350, "fist", 0.9).is_empty());
+        let events = feed(&mut smoother, base, 400, 400, "fist", 0.9);
+        match events.as_slice() {
+            [(400, SmoothedGestureEvent::Start { gesture_type, confidence })] => {
+                assert_eq!(gesture_type, "fist");
+                assert!((confidence - 0.9).abs() < 1e-9);
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn does_not_start_below_upper_hysteresis_bound() {
+        let base = Instant::now();
+        let mut smoother = GestureSmoother::default();
+
+        assert!(feed(&mut smoother, base, 0, 2000, "fist", THRESHOLD + 0.03).is_empty());
+        assert!(feed(&mut smoother, base, 2050, 3000, "five", 0.99).is_empty());
+    }
+
+    #[test]
+    fn holds_above_lower_hysteresis_bound() {
+        let base = Instant::now();
+        let mut smoother = GestureSmoother::default();
+        started(&mut smoother, base);
+
+        // 低于阈值但仍在滞回范围内，手势保持
+        assert!(feed(&mut smoother, base, 450, 2000, "fist", THRESHOLD - 0.03).is_empty());
+    }
+
+    #[test]
+    fn releases_after_absence() {
+        let base = Instant::now();
+        let mut smoother = GestureSmoother::default();
+        started(&mut smoother, base);
+
+        // 最后一次被支持在400ms，低于下限的帧从450ms开始，800ms时结束
+        let events = feed(&mut smoother, base, 450, 1000, "fist", THRESHOLD - 0.1);
+        assert_eq!(events.len(), 1);
+        let (at, event) = &events[0];
+        assert_eq!(*at, 800);
+        match event {
+            SmoothedGestureEvent::End { gesture_type, duration_ms, .. } => {
+                assert_eq!(gesture_type, "fist");
+                // 从候选开始（100ms）到最后一次被支持（400ms）
+                assert_eq!(*duration_ms, 300);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tick_ends_gesture_without_new_frames() {
+        let base = Instant::now();
+        let mut smoother = GestureSmoother::default();
+        started(&mut smoother, base);
+
+        assert!(smoother.tick(base + Duration::from_millis(750)).is_empty());
+        let events = smoother.tick(base + Duration::from_millis(800));
+        assert!(matches!(events.as_slice(), [SmoothedGestureEvent::End { duration_ms: 300, .. }]));
+        assert!(smoother.tick(base + Duration::from_millis(900)).is_empty());
+    }
+
+    #[test]
+    fn idle_only_without_active_gesture() {
+        let base = Instant::now();
+        let mut smoother = GestureSmoother::default();
+        assert!(smoother.is_idle(base, SESSION_IDLE_TIMEOUT));
+
+        started(&mut smoother, base);
+        let later = base + SESSION_IDLE_TIMEOUT * 2;
+        assert!(!smoother.is_idle(later, SESSION_IDLE_TIMEOUT));
+
+        smoother.tick(later);
+        assert!(smoother.is_idle(later, SESSION_IDLE_TIMEOUT));
+    }
+}
@@ -0,0 +1,264 @@
+// 手势统计 - 按时间分段、置信度分布与混淆矩阵
+use serde::{Deserialize, Serialize};
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::{Row, Sqlite};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::gesture_service::normalize_sql_timestamp;
+
+// 按detected_at过滤的条件，参数由DateRange::bind按顺序绑定
+pub(crate) const RANGE_FILTER: &str = "(? IS NULL OR detected_at >= ?) AND (? IS NULL OR detected_at <= ?)";
+
+// 可选的统计时间范围
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DateRange {
+    start: Option<String>,
+    end: Option<String>,
+}
+
+impl DateRange {
+    pub(crate) fn new(start: Option<String>, end: Option<String>) -> Self {
+        Self {
+            start: start.as_deref().map(normalize_sql_timestamp),
+            end: end.as_deref().map(normalize_sql_timestamp),
+        }
+    }
+
+    pub(crate) fn bind<'q>(&'q self, query: Query<'q, Sqlite, SqliteArguments<'q>>) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(&self.start)
+            .bind(&self.start)
+            .bind(&self.end)
+            .bind(&self.end)
+    }
+}
+
+// 时间分段的粒度
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsBucket {
+    Hour,
+    Day,
+}
+
+impl StatsBucket {
+    fn format(&self) -> &'static str {
+        match self {
+            StatsBucket::Hour => "%Y-%m-%d %H:00",
+            StatsBucket::Day => "%Y-%m-%d",
+        }
+    }
+}
+
+// 某个时间段内某个手势的统计
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestureTimeBucket {
+    pub bucket: String,
+    pub gesture_type: String,
+    pub count: i64,
+    pub average_confidence: f64,
+}
+
+// 单个手势的置信度分布
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestureConfidenceStats {
+    pub gesture_type: String,
+    pub count: usize,
+    pub average_confidence: f64,
+    pub p10_confidence: f64,
+    pub p90_confidence: f64,
+    pub threshold: Option<f64>, // 当前配置的阈值
+    // 置信度达到识别时阈值的记录占比，没有阈值信息时为None
+    pub above_threshold_ratio: Option<f64>,
+}
+
+// 混淆矩阵：matrix[i][j]为实际手势labels[i]被识别为labels[j]的次数
+// 用户纠正过的记录以纠正后的标签为实际手势，其余记录视为识别正确
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestureConfusionMatrix {
+    pub labels: Vec<String>,
+    pub matrix: Vec<Vec<i64>>,
+    pub total_records: i64,
+    pub corrected_records: i64,
+}
+
+// 同一手势的 (置信度, 识别时阈值) 列表及当前阈值
+#[derive(Default)]
+struct ConfidenceGroup {
+    records: Vec<(f64, Option<f64>)>,
+    current_threshold: Option<f64>,
+}
+
+// 线性插值的分位数，values需已排序
+fn percentile(values: &[f64], p: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let rank = p * (values.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    values[low] + (values[high] - values[low]) * (rank - low as f64)
+}
+
+// Tauri命令：按小时或天分段统计各手势的检测次数
+#[tauri::command]
+pub async fn get_gesture_time_buckets(
+    bucket: StatsBucket,
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<Vec<GestureTimeBucket>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let range = DateRange::new(start, end);
+
+    let sql = format!(
+        r#"
+        SELECT strftime(?, detected_at) AS bucket, gesture_type, COUNT(*) AS count, AVG(confidence) AS avg_conf
+        FROM gesture_records
+        WHERE {}
+        GROUP BY bucket, gesture_type
+        ORDER BY bucket, gesture_type
+        "#,
+        RANGE_FILTER
+    );
+    let rows = range.bind(sqlx::query(&sql).bind(bucket.format()))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| GestureTimeBucket {
+            bucket: row.get("bucket"),
+            gesture_type: row.get("gesture_type"),
+            count: row.get("count"),
+            average_confidence: row.get::<Option<f64>, _>("avg_conf").unwrap_or(0.0),
+        })
+        .collect())
+}
+
+// Tauri命令：各手势的平均置信度、p10/p90及达到阈值的比例
+#[tauri::command]
+pub async fn get_gesture_confidence_stats(
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<Vec<GestureConfidenceStats>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let range = DateRange::new(start, end);
+
+    let sql = format!(
+        r#"
+        SELECT r.gesture_type, r.confidence, COALESCE(r.threshold, c.threshold) AS threshold,
+               c.threshold AS current_threshold
+        FROM gesture_records r
+        LEFT JOIN gesture_configs c ON c.gesture_type = r.gesture_type
+        WHERE {}
+        ORDER BY r.gesture_type, r.confidence
+        "#,
+        RANGE_FILTER
+    );
+    let rows = range.bind(sqlx::query(&sql))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 每条记录按其识别时的阈值判断，旧记录没有保存阈值时使用当前配置
+    let mut grouped: BTreeMap<String, ConfidenceGroup> = BTreeMap::new();
+    for row in rows {
+        let group = grouped.entry(row.get("gesture_type")).or_default();
+        group.records.push((row.get("confidence"), row.get("threshold")));
+        group.current_threshold = row.get("current_threshold");
+    }
+
+    Ok(grouped
+        .into_iter()
+        .map(|(gesture_type, ConfidenceGroup { records, current_threshold: threshold })| {
+            let count = records.len();
+            let confidences: Vec<f64> = records.iter().map(|(confidence, _)| *confidence).collect();
+            let with_threshold: Vec<(f64, f64)> = records
+                .iter()
+                .filter_map(|(confidence, threshold)| threshold.map(|t| (*confidence, t)))
+                .collect();
+            let above_threshold_ratio = (!with_threshold.is_empty()).then(|| {
+                with_threshold.iter().filter(|(c, t)| c >= t).count() as f64 / with_threshold.len() as f64
+            });
+            GestureConfidenceStats {
+                gesture_type,
+                count,
+                average_confidence: confidences.iter().sum::<f64>() / count as f64,
+                p10_confidence: percentile(&confidences, 0.1),
+                p90_confidence: percentile(&confidences, 0.9),
+                threshold,
+                above_threshold_ratio,
+            }
+        })
+        .collect())
+}
+
+// Tauri命令：根据用户纠正生成混淆矩阵
+#[tauri::command]
+pub async fn get_gesture_confusion_matrix(
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<GestureConfusionMatrix, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let range = DateRange::new(start, end);
+
+    let sql = format!(
+        r#"
+        SELECT COALESCE(corrected_type, gesture_type) AS actual, gesture_type AS predicted,
+               COUNT(*) AS count, SUM(corrected_type IS NOT NULL) AS corrected
+        FROM gesture_records
+        WHERE {}
+        GROUP BY actual, predicted
+        "#,
+        RANGE_FILTER
+    );
+    let rows = range.bind(sqlx::query(&sql))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let cells: Vec<(String, String, i64, i64)> = rows
+        .into_iter()
+        .map(|row| (row.get("actual"), row.get("predicted"), row.get("count"), row.get("corrected")))
+        .collect();
+
+    let labels: Vec<String> = cells
+        .iter()
+        .flat_map(|(actual, predicted, _, _)| [actual.clone(), predicted.clone()])
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let index = |label: &str| labels.iter().position(|l| l == label).unwrap_or_default();
+
+    let mut matrix = vec![vec![0; labels.len()]; labels.len()];
+    let mut total_records = 0;
+    let mut corrected_records = 0;
+    for (actual, predicted, count, corrected) in &cells {
+        matrix[index(actual)][index(predicted)] += count;
+        total_records += count;
+        corrected_records += corrected;
+    }
+
+    Ok(GestureConfusionMatrix {
+        labels,
+        matrix,
+        total_records,
+        corrected_records,
+    })
+}
@@ -0,0 +1,341 @@
+// 自定义手势模板 - 用户录入关键点样本，通过最近邻匹配识别新手势
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::gesture_keypoints::{decode_landmarks, encode_landmarks, validate_landmarks, KeypointError};
+use crate::gesture_service::{Landmark, HAND_LANDMARK_COUNT};
+
+// 手腕与中指根部的索引，用于确定手的位置、大小和朝向
+const WRIST: usize = 0;
+const MIDDLE_MCP: usize = 9;
+
+// 归一化后平均关键点距离小于该值视为完全匹配，大于上限视为不匹配
+const MATCH_DISTANCE_PERFECT: f64 = 0.03;
+const MATCH_DISTANCE_NONE: f64 = 0.25;
+
+// 已录入的模板缓存，分类时无需访问数据库
+static TEMPLATE_CACHE: Lazy<Mutex<Vec<GestureTemplate>>> = Lazy::new(|| {
+    Mutex::new(Vec::new())
+});
+
+// 手势模板结构
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestureTemplate {
+    pub id: Option<i64>,
+    pub config_id: i64,
+    pub gesture_type: String,
+    pub record_id: Option<i64>,
+    pub landmarks: Vec<Landmark>, // 归一化后的关键点
+    pub created_at: Option<String>,
+}
+
+// 初始化手势模板表
+pub async fn init_gesture_template_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS gesture_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            config_id INTEGER NOT NULL,
+            record_id INTEGER,
+            landmarks TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (config_id) REFERENCES gesture_configs(id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_gesture_templates_config ON gesture_templates(config_id)")
+        .execute(pool)
+        .await?;
+
+    reload_template_cache(pool).await
+}
+
+// 从数据库重新加载模板缓存
+pub async fn reload_template_cache(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let templates = load_templates(pool, None).await?;
+    if let Ok(mut cache) = TEMPLATE_CACHE.lock() {
+        *cache = templates;
+    }
+    Ok(())
+}
+
+async fn load_templates(pool: &SqlitePool, config_id: Option<i64>) -> Result<Vec<GestureTemplate>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT t.id, t.config_id, c.gesture_type, t.record_id, t.landmarks, t.created_at
+        FROM gesture_templates t
+        JOIN gesture_configs c ON c.id = t.config_id
+        WHERE ? IS NULL OR t.config_id = ?
+        ORDER BY t.id
+        "#,
+    )
+    .bind(config_id)
+    .bind(config_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut templates = Vec::new();
+    for row in rows {
+        let landmarks: String = row.get("landmarks");
+        let Ok(landmarks) = serde_json::from_str(&landmarks) else { continue };
+        templates.push(GestureTemplate {
+            id: Some(row.get("id")),
+            config_id: row.get("config_id"),
+            gesture_type: row.get("gesture_type"),
+            record_id: row.get("record_id"),
+            landmarks,
+            created_at: row.get("created_at"),
+        });
+    }
+
+    Ok(templates)
+}
+
+// 删除某个手势配置的全部模板，供调用方在事务中使用，提交后需调用reload_template_cache
+pub async fn delete_config_templates(conn: &mut SqliteConnection, config_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM gesture_templates WHERE config_id = ?")
+        .bind(config_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// 归一化关键点：平移到手腕为原点，按手掌大小缩放，并旋转到手腕指向中指根部朝上
+pub fn normalize_landmarks(lm: &[Landmark]) -> Result<Vec<Landmark>, String> {
+    if lm.len() != HAND_LANDMARK_COUNT {
+        return Err(format!("需要{}个关键点，实际为{}", HAND_LANDMARK_COUNT, lm.len()));
+    }
+
+    let origin = lm[WRIST];
+    let axis = (lm[MIDDLE_MCP].x - origin.x, lm[MIDDLE_MCP].y - origin.y, lm[MIDDLE_MCP].z - origin.z);
+    let palm_size = (axis.0.powi(2) + axis.1.powi(2) + axis.2.powi(2)).sqrt();
+    if !palm_size.is_finite() || palm_size <= f64::EPSILON {
+        return Err("关键点数据无效".to_string());
+    }
+
+    // 只在图像平面内旋转，使手腕→中指根部对齐到y轴负方向（图像中的“向上”）
+    let angle = axis.0.atan2(-axis.1);
+    let (sin, cos) = angle.sin_cos();
+
+    Ok(lm
+        .iter()
+        .map(|point| {
+            let x = (point.x - origin.x) / palm_size;
+            let y = (point.y - origin.y) / palm_size;
+            Landmark {
+                x: x * cos + y * sin,
+                y: -x * sin + y * cos,
+                z: (point.z - origin.z) / palm_size,
+            }
+        })
+        .collect())
+}
+
+// 两组归一化关键点的平均距离
+fn landmark_distance(a: &[Landmark], b: &[Landmark]) -> f64 {
+    let total: f64 = a
+        .iter()
+        .zip(b)
+        .map(|(p, q)| ((p.x - q.x).powi(2) + (p.y - q.y).powi(2) + (p.z - q.z).powi(2)).sqrt())
+        .sum();
+    total / a.len().max(1) as f64
+}
+
+// 距离转换为0~1的相似度
+fn distance_to_confidence(distance: f64) -> f64 {
+    1.0 - ((distance - MATCH_DISTANCE_PERFECT) / (MATCH_DISTANCE_NONE - MATCH_DISTANCE_PERFECT)).clamp(0.0, 1.0)
+}
+
+// 计算每种自定义手势的置信度：取与该手势最近的模板（1-NN）
+pub fn score_custom_gestures(lm: &[Landmark]) -> BTreeMap<String, f64> {
+    let mut scores = BTreeMap::new();
+    let Ok(normalized) = normalize_landmarks(lm) else { return scores };
+    let Ok(cache) = TEMPLATE_CACHE.lock() else { return scores };
+
+    for template in cache.iter() {
+        let confidence = distance_to_confidence(landmark_distance(&normalized, &template.landmarks));
+        let best = scores.entry(template.gesture_type.clone()).or_insert(0.0);
+        if confidence > *best {
+            *best = confidence;
+        }
+    }
+
+    scores
+}
+
+// 保存已归一化的模板，归一化需在写入任何数据之前完成
+async fn insert_template(
+    conn: &mut SqliteConnection,
+    config_id: i64,
+    record_id: Option<i64>,
+    normalized: &[Landmark],
+) -> Result<i64, String> {
+    let json = serde_json::to_string(normalized).map_err(|e| e.to_string())?;
+
+    let id: i64 = sqlx::query(
+        "INSERT INTO gesture_templates (config_id, record_id, landmarks) VALUES (?, ?, ?) RETURNING id"
+    )
+    .bind(config_id)
+    .bind(record_id)
+    .bind(json)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| e.to_string())?
+    .get("id");
+
+    Ok(id)
+}
+
+// 自定义手势的类型名，找不到或不是自定义手势时报错
+async fn custom_gesture_type(pool: &SqlitePool, config_id: i64) -> Result<String, String> {
+    let row = sqlx::query("SELECT gesture_type, custom FROM gesture_configs WHERE id = ?")
+        .bind(config_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("手势配置不存在")?;
+
+    if !row.get::<bool, _>("custom") {
+        return Err("内置手势不支持录入样本".to_string());
+    }
+    Ok(row.get("gesture_type"))
+}
+
+// Tauri命令：从已保存的关键点数据中录入样本
+#[tauri::command]
+pub async fn enroll_gesture_samples(config_id: i64, record_ids: Vec<i64>) -> Result<Vec<GestureTemplate>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    custom_gesture_type(pool, config_id).await?;
+
+    // 先读取并校验全部样本，任一条无效都不写入
+    let mut samples = Vec::with_capacity(record_ids.len());
+    for record_id in record_ids {
+        let keypoints: Vec<u8> = sqlx::query(
+            "SELECT keypoints FROM keypoint_data WHERE record_id = ? ORDER BY id DESC LIMIT 1"
+        )
+        .bind(record_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("记录{}没有关键点数据", record_id))?
+        .get("keypoints");
+
+        let normalized = decode_landmarks(&keypoints)
+            .map_err(|e| e.to_string())
+            .and_then(|landmarks| normalize_landmarks(&landmarks))
+            .map_err(|e| format!("记录{}: {}", record_id, e))?;
+        samples.push((record_id, normalized));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for (record_id, normalized) in &samples {
+        insert_template(&mut tx, config_id, Some(*record_id), normalized)
+            .await
+            .map_err(|e| format!("记录{}: {}", record_id, e))?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    reload_template_cache(pool).await.map_err(|e| e.to_string())?;
+    load_templates(pool, Some(config_id)).await.map_err(|e| e.to_string())
+}
+
+// Tauri命令：直接采集当前帧作为样本
+// 新手势在录入前无法被识别，不会自动产生关键点数据，因此采集时同时保存一条记录及其关键点
+#[tauri::command]
+pub async fn capture_gesture_sample(
+    config_id: i64,
+    keypoints: Vec<Landmark>,
+    frame_width: i32,
+    frame_height: i32,
+) -> Result<GestureTemplate, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let gesture_type = custom_gesture_type(pool, config_id).await?;
+    // 先校验，避免保存无效样本
+    validate_landmarks(&keypoints).map_err(|e| e.to_string())?;
+    if frame_width <= 0 || frame_height <= 0 {
+        return Err(KeypointError::InvalidFrameSize { width: frame_width, height: frame_height }.to_string());
+    }
+
+    let normalized = normalize_landmarks(&keypoints)?;
+
+    // 记录、关键点和模板在同一事务中写入，任一步失败都不会留下孤立数据
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let record_id: i64 = sqlx::query(
+        "INSERT INTO gesture_records (gesture_type, confidence, config_id) VALUES (?, 1.0, ?) RETURNING id"
+    )
+    .bind(&gesture_type)
+    .bind(config_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?
+    .get("id");
+
+    sqlx::query(
+        "INSERT INTO keypoint_data (record_id, keypoints, frame_width, frame_height) VALUES (?, ?, ?, ?)"
+    )
+    .bind(record_id)
+    .bind(encode_landmarks(&keypoints))
+    .bind(frame_width)
+    .bind(frame_height)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let id = insert_template(&mut tx, config_id, Some(record_id), &normalized).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    reload_template_cache(pool).await.map_err(|e| e.to_string())?;
+
+    load_templates(pool, Some(config_id))
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|template| template.id == Some(id))
+        .ok_or_else(|| "保存样本失败".to_string())
+}
+
+// Tauri命令：获取手势的已录入模板
+#[tauri::command]
+pub async fn get_gesture_templates(config_id: Option<i64>) -> Result<Vec<GestureTemplate>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    load_templates(db.get_pool(), config_id).await.map_err(|e| e.to_string())
+}
+
+// Tauri命令：删除单个模板
+#[tauri::command]
+pub async fn delete_gesture_template(id: i64) -> Result<bool, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let result = sqlx::query("DELETE FROM gesture_templates WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    reload_template_cache(pool).await.map_err(|e| e.to_string())?;
+    Ok(result.rows_affected() > 0)
+}
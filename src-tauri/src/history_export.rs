@@ -0,0 +1,387 @@
+// 优化历史导出 - 按搜索相同的过滤条件导出为Markdown、CSV或带版本号的JSON
+// 多模型优化记录和导入的历史中成功的结果也保存在optimization_history中，一并导出
+// 分批读取后交给阻塞线程写入文件，导出大量记录时不会一次性加载到内存，也不阻塞异步运行时
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+use crate::history_search::{HistoryFilters, HistoryQuery};
+use crate::services::{optimization_history_from_row, OptimizationHistory};
+
+// JSON导出格式的版本号，字段变化时递增
+pub const HISTORY_EXPORT_VERSION: u32 = 1;
+
+const EXPORT_BATCH_SIZE: i64 = 500;
+// 等待写入的批次数，写入较慢时读取暂停
+const EXPORT_QUEUE_BATCHES: usize = 2;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryExportFormat {
+    Markdown,
+    Csv,
+    Json,
+}
+
+// 导出请求，text和filters与搜索一致，ids非空时只导出选中的记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryExportRequest {
+    pub path: String,
+    pub format: HistoryExportFormat,
+    pub text: Option<String>,
+    #[serde(flatten)]
+    pub filters: HistoryFilters,
+    #[serde(default)]
+    pub ids: Vec<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryExportResult {
+    pub path: String,
+    pub format: HistoryExportFormat,
+    pub count: usize,
+}
+
+// JSON导出的文件头，记录之后以items数组逐条写入
+#[derive(Serialize)]
+struct JsonExportHeader<'a> {
+    version: u32,
+    exported_at: String,
+    text: Option<&'a str>,
+    filters: &'a HistoryFilters,
+}
+
+// 按格式逐条写入记录
+enum ExportWriter<W: Write> {
+    Markdown(BufWriter<W>),
+    Csv(Box<csv::Writer<BufWriter<W>>>),
+    Json { out: BufWriter<W>, first: bool },
+}
+
+impl<W: Write> ExportWriter<W> {
+    fn create(inner: W, request: &HistoryExportRequest) -> anyhow::Result<Self> {
+        let mut out = BufWriter::new(inner);
+        match request.format {
+            HistoryExportFormat::Markdown => {
+                writeln!(out, "# CodingPal 提示词优化记录\n")?;
+                writeln!(out, "> 导出时间: {}\n", Utc::now().format("%Y-%m-%d %H:%M:%S UTC"))?;
+                Ok(ExportWriter::Markdown(out))
+            }
+            HistoryExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(out);
+                writer.write_record([
+                    "id",
+                    "created_at",
+                    "model",
+                    "task_id",
+                    "rating",
+                    "confidence",
+                    "tokens_used",
+                    "processing_time_ms",
+                    "original_prompt",
+                    "optimized_prompt",
+                    "improvements",
+                ])?;
+                Ok(ExportWriter::Csv(Box::new(writer)))
+            }
+            HistoryExportFormat::Json => {
+                let header = JsonExportHeader {
+                    version: HISTORY_EXPORT_VERSION,
+                    exported_at: Utc::now().to_rfc3339(),
+                    text: request.text.as_deref(),
+                    filters: &request.filters,
+                };
+                // 去掉头部对象的右括号，接着写入items数组
+                let header = serde_json::to_string(&header)?;
+                let header = header.strip_suffix('}').unwrap_or(&header);
+                write!(out, "{},\"items\":[", header)?;
+                Ok(ExportWriter::Json { out, first: true })
+            }
+        }
+    }
+
+    fn write(&mut self, history: &OptimizationHistory) -> anyhow::Result<()> {
+        match self {
+            ExportWriter::Markdown(out) => write_markdown(out, history),
+            ExportWriter::Csv(writer) => {
+                writer.write_record([
+                    history.id.to_string(),
+                    history.created_at.to_rfc3339(),
+                    history.model.clone().unwrap_or_default(),
+                    history.task_id.map(|id| id.to_string()).unwrap_or_default(),
+                    history.rating.map(|r| r.to_string()).unwrap_or_default(),
+                    history.confidence.to_string(),
+                    history.tokens_used.to_string(),
+                    history.processing_time_ms.to_string(),
+                    history.original_prompt.clone(),
+                    history.optimized_prompt.clone(),
+                    history.improvements.join("; "),
+                ])?;
+                Ok(())
+            }
+            ExportWriter::Json { out, first } => {
+                if !*first {
+                    out.write_all(b",")?;
+                }
+                *first = false;
+                serde_json::to_writer(&mut *out, history)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self) -> anyhow::Result<W> {
+        let out = match self {
+            ExportWriter::Markdown(out) => out,
+            ExportWriter::Csv(writer) => writer.into_inner().map_err(|e| e.into_error())?,
+            ExportWriter::Json { mut out, .. } => {
+                out.write_all(b"]}\n")?;
+                out
+            }
+        };
+        Ok(out.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
+// 代码块围栏比内容中最长的连续反引号多一个，避免提前闭合
+fn code_fence(content: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    "`".repeat((longest + 1).max(3))
+}
+
+fn write_markdown(out: &mut impl Write, history: &OptimizationHistory) -> anyhow::Result<()> {
+    writeln!(
+        out,
+        "## {} · {}\n",
+        history.created_at.format("%Y-%m-%d %H:%M"),
+        history.model.as_deref().unwrap_or("未知模型")
+    )?;
+
+    let mut meta = vec![format!("Token: {}", history.tokens_used), format!("耗时: {}ms", history.processing_time_ms)];
+    if let Some(rating) = history.rating {
+        meta.insert(0, format!("评分: {}/5", rating));
+    }
+    writeln!(out, "{}\n", meta.join(" · "))?;
+
+    for (title, content) in [("优化前", &history.original_prompt), ("优化后", &history.optimized_prompt)] {
+        let fence = code_fence(content);
+        writeln!(out, "### {}\n\n{}text\n{}\n{}\n", title, fence, content.trim_end(), fence)?;
+    }
+
+    if !history.improvements.is_empty() {
+        writeln!(out, "### 改进点\n")?;
+        for improvement in &history.improvements {
+            writeln!(out, "- {}", improvement)?;
+        }
+        writeln!(out)?;
+    }
+    writeln!(out, "---\n")?;
+    Ok(())
+}
+
+// 按 (created_at, id) 倒序分批读取，每批从上一批最后一条之后继续，写入端关闭时停止读取
+async fn export_history(
+    pool: &SqlitePool,
+    request: &HistoryExportRequest,
+    batches: mpsc::Sender<Vec<OptimizationHistory>>,
+) -> anyhow::Result<usize> {
+    let history_query = HistoryQuery::new(request.text.as_deref(), &request.filters, &request.ids);
+    let sql = format!(
+        r#"
+        SELECT * FROM ({base})
+        WHERE (? IS NULL OR created_at < ? OR (created_at = ? AND id < ?))
+        ORDER BY created_at DESC, id DESC
+        LIMIT ?
+        "#,
+        base = history_query.sql()
+    );
+
+    let mut count = 0;
+    let mut cursor: Option<(String, i64)> = None;
+    loop {
+        let cursor_created_at = cursor.as_ref().map(|(created_at, _)| created_at.clone());
+        let cursor_id = cursor.as_ref().map(|(_, id)| *id);
+        let rows = history_query.bind(sqlx::query(&sql))
+            .bind(&cursor_created_at)
+            .bind(&cursor_created_at)
+            .bind(&cursor_created_at)
+            .bind(cursor_id)
+            .bind(EXPORT_BATCH_SIZE)
+            .fetch_all(pool)
+            .await?;
+
+        count += rows.len();
+        let next = rows.last()
+            .filter(|_| rows.len() as i64 == EXPORT_BATCH_SIZE)
+            .map(|last| (last.get("created_at"), last.get("id")));
+        if !rows.is_empty() && batches.send(rows.iter().map(optimization_history_from_row).collect()).await.is_err() {
+            break;
+        }
+
+        match next {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    Ok(count)
+}
+
+// 在阻塞线程中创建临时文件并写入收到的每一批记录
+fn write_export(
+    request: HistoryExportRequest,
+    temp_path: PathBuf,
+    mut batches: mpsc::Receiver<Vec<OptimizationHistory>>,
+) -> anyhow::Result<()> {
+    let file = File::create(&temp_path).map_err(|e| anyhow::anyhow!("创建导出文件失败: {}", e))?;
+    let mut writer = ExportWriter::create(file, &request)?;
+    while let Some(batch) = batches.blocking_recv() {
+        for history in &batch {
+            writer.write(history)?;
+        }
+    }
+    writer.finish()?.sync_all()?;
+    Ok(())
+}
+
+// 先写入临时文件，完成后再替换目标文件，失败时不留下不完整的导出
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+// Tauri命令：导出优化历史到用户选择的文件
+#[tauri::command]
+pub async fn export_optimization_history(request: HistoryExportRequest) -> Result<HistoryExportResult, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    let path = PathBuf::from(&request.path);
+    if path.file_name().is_none() {
+        return Err("导出路径无效".to_string());
+    }
+    let temp_path = partial_path(&path);
+
+    let (sender, receiver) = mpsc::channel(EXPORT_QUEUE_BATCHES);
+    let writing = tokio::task::spawn_blocking({
+        let request = request.clone();
+        let temp_path = temp_path.clone();
+        move || write_export(request, temp_path, receiver)
+    });
+    // 读取结束后发送端被释放，写入端随之完成；读取和写入都成功才替换目标文件
+    let read = export_history(db.get_pool(), &request, sender).await;
+    let written = writing.await.map_err(anyhow::Error::from).and_then(|result| result);
+    let exported = written.and(read).and_then(|count| {
+        std::fs::rename(&temp_path, &path)?;
+        Ok(count)
+    });
+
+    match exported {
+        Ok(count) => Ok(HistoryExportResult {
+            path: request.path,
+            format: request.format,
+            count,
+        }),
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp_path);
+            Err(format!("导出优化历史失败: {}", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn request(format: HistoryExportFormat) -> HistoryExportRequest {
+        HistoryExportRequest {
+            path: "export".to_string(),
+            format,
+            text: Some("排序 \"quoted\"".to_string()),
+            filters: HistoryFilters { model: Some("glm-4".to_string()), ..Default::default() },
+            ids: Vec::new(),
+        }
+    }
+
+    fn history(id: i64, original: &str, optimized: &str) -> OptimizationHistory {
+        OptimizationHistory {
+            id,
+            original_prompt: original.to_string(),
+            optimized_prompt: optimized.to_string(),
+            confidence: 0.85,
+            tokens_used: 120,
+            processing_time_ms: 900,
+            task_id: None,
+            model: Some("glm-4".to_string()),
+            rating: Some(4),
+            improvements: vec!["更具体".to_string(), "补充约束".to_string()],
+            created_at: Utc.with_ymd_and_hms(2024, 5, 1, 8, 0, 0).unwrap(),
+        }
+    }
+
+    fn export(format: HistoryExportFormat, items: &[OptimizationHistory]) -> String {
+        let mut writer = ExportWriter::create(Vec::new(), &request(format)).unwrap();
+        for item in items {
+            writer.write(item).unwrap();
+        }
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn code_fence_is_longer_than_content_backticks() {
+        assert_eq!(code_fence("plain"), "```");
+        assert_eq!(code_fence("inline `code`"), "```");
+        assert_eq!(code_fence("```rust\nfn main() {}\n```"), "````");
+        assert_eq!(code_fence("a ````` b"), "``````");
+    }
+
+    #[test]
+    fn markdown_keeps_nested_fences_closed() {
+        let output = export(HistoryExportFormat::Markdown, &[history(1, "```\nold\n```", "new")]);
+        assert!(output.contains("### 优化前\n\n````text\n```\nold\n```\n````"));
+        assert!(output.contains("评分: 4/5"));
+        assert!(output.contains("- 补充约束"));
+    }
+
+    #[test]
+    fn json_header_and_items_form_one_document() {
+        let output = export(HistoryExportFormat::Json, &[history(1, "a", "b"), history(2, "c", "d")]);
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["version"], HISTORY_EXPORT_VERSION);
+        assert_eq!(value["text"], "排序 \"quoted\"");
+        assert_eq!(value["filters"]["model"], "glm-4");
+        assert_eq!(value["items"].as_array().unwrap().len(), 2);
+        assert_eq!(value["items"][1]["original_prompt"], "c");
+
+        let empty: serde_json::Value = serde_json::from_str(&export(HistoryExportFormat::Json, &[])).unwrap();
+        assert!(empty["items"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn csv_escapes_quotes_commas_and_newlines() {
+        let output = export(HistoryExportFormat::Csv, &[history(7, "a, \"b\"\nc", "plain")]);
+        let mut reader = csv::Reader::from_reader(output.as_bytes());
+        let headers = reader.headers().unwrap().clone();
+        let record = reader.records().next().unwrap().unwrap();
+
+        assert_eq!(headers.len(), record.len());
+        assert_eq!(&record[0], "7");
+        assert_eq!(&record[8], "a, \"b\"\nc");
+        assert_eq!(&record[10], "更具体; 补充约束");
+        assert!(output.contains("\"a, \"\"b\"\"\nc\""));
+    }
+}
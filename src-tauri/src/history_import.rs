@@ -0,0 +1,275 @@
+// 历史记录导入 - 将前端localStorage中导出的历史记录和偏好设置一次性迁移到数据库
+// 支持HistoryService.exportHistory导出的记录数组和StorageService.exportData导出的对象
+// 记录通过save_record保存，收藏标记一并保留，成功的结果同时出现在历史搜索和导出中
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Row, SqlitePool};
+use std::collections::HashSet;
+
+use crate::optimization_records::{content_hash, save_record, OptimizationRecord};
+use crate::services::DatabaseService;
+
+// 导入完成后记录导入时间，前端据此判断是否还需要迁移
+pub const LOCAL_HISTORY_IMPORTED_SETTING: &str = "local_history_imported_at";
+
+// 偏好设置以该前缀加蛇形命名的字段名保存，如preferredModels保存为preference_preferred_models
+const PREFERENCE_SETTING_PREFIX: &str = "preference_";
+
+// 报告中最多保留的失败原因条数
+const MAX_IMPORT_ERRORS: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HistoryImportReport {
+    pub imported: usize,
+    pub skipped: usize, // ID或内容与已有记录重复
+    pub failed: usize,
+    pub errors: Vec<String>,
+    pub settings_imported: usize,
+    pub settings_skipped: usize, // 数据库中已有同名设置
+    pub configs_skipped: usize, // API配置包含密钥，不自动导入，需在设置中重新填写
+}
+
+impl HistoryImportReport {
+    fn fail(&mut self, index: usize, reason: impl std::fmt::Display) {
+        self.failed += 1;
+        if self.errors.len() < MAX_IMPORT_ERRORS {
+            self.errors.push(format!("第{}条记录: {}", index + 1, reason));
+        }
+    }
+}
+
+// 导出数据中的各部分，exportHistory导出的数组只包含历史记录
+#[derive(Default)]
+struct LocalExport {
+    history: Vec<Value>,
+    preferences: serde_json::Map<String, Value>,
+    configs: usize,
+}
+
+fn parse_export(data: Value) -> Result<LocalExport, String> {
+    let mut object = match data {
+        Value::Array(history) => return Ok(LocalExport { history, ..Default::default() }),
+        Value::Object(object) => object,
+        _ => return Err("无法识别的导入数据格式".to_string()),
+    };
+
+    let history = match object.remove("history") {
+        Some(Value::Array(entries)) => entries,
+        Some(Value::Null) | None => Vec::new(),
+        Some(_) => return Err("history字段不是数组".to_string()),
+    };
+    let preferences = match object.remove("preferences") {
+        Some(Value::Object(preferences)) => preferences,
+        Some(Value::Null) | None => serde_json::Map::new(),
+        Some(_) => return Err("preferences字段不是对象".to_string()),
+    };
+    let configs = match object.remove("configs") {
+        Some(Value::Array(configs)) => configs.len(),
+        _ => 0,
+    };
+
+    Ok(LocalExport { history, preferences, configs })
+}
+
+fn preference_setting_key(name: &str) -> String {
+    let mut key = PREFERENCE_SETTING_PREFIX.to_string();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            key.push('_');
+            key.push(c.to_ascii_lowercase());
+        } else {
+            key.push(c);
+        }
+    }
+    key
+}
+
+// 字符串原样保存，其余类型保存为JSON文本
+fn preference_setting_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+// 导入偏好设置，已存在的设置以数据库为准
+async fn import_preferences(
+    db: &DatabaseService,
+    preferences: serde_json::Map<String, Value>,
+    report: &mut HistoryImportReport,
+) -> Result<(), String> {
+    for (name, value) in preferences {
+        let key = preference_setting_key(&name);
+        if db.get_setting(&key).await.map_err(|e| e.to_string())?.is_some() {
+            report.settings_skipped += 1;
+            continue;
+        }
+        db.set_setting(&key, &preference_setting_value(&value))
+            .await
+            .map_err(|e| e.to_string())?;
+        report.settings_imported += 1;
+    }
+    Ok(())
+}
+
+async fn existing_keys(pool: &SqlitePool) -> Result<(HashSet<String>, HashSet<String>), sqlx::Error> {
+    let rows = sqlx::query("SELECT id, content_hash FROM optimization_requests")
+        .fetch_all(pool)
+        .await?;
+
+    let mut ids = HashSet::new();
+    let mut hashes = HashSet::new();
+    for row in rows {
+        ids.insert(row.get::<String, _>("id"));
+        hashes.insert(row.get::<String, _>("content_hash"));
+    }
+    Ok((ids, hashes))
+}
+
+pub async fn import_history(db: &DatabaseService, data: Value) -> Result<HistoryImportReport, String> {
+    let pool = db.get_pool();
+    let export = parse_export(data)?;
+    let (mut ids, mut hashes) = existing_keys(pool).await.map_err(|e| e.to_string())?;
+    let mut report = HistoryImportReport {
+        configs_skipped: export.configs,
+        ..Default::default()
+    };
+
+    import_preferences(db, export.preferences, &mut report).await?;
+
+    for (index, entry) in export.history.into_iter().enumerate() {
+        let record: OptimizationRecord = match serde_json::from_value(entry) {
+            Ok(record) => record,
+            Err(e) => {
+                report.fail(index, e);
+                continue;
+            }
+        };
+        if record.original_prompt.trim().is_empty() {
+            report.fail(index, "原始提示词为空");
+            continue;
+        }
+
+        let hash = content_hash(&record);
+        if ids.contains(&record.id) || hashes.contains(&hash) {
+            report.skipped += 1;
+            continue;
+        }
+
+        match save_record(pool, record).await {
+            Ok(id) => {
+                ids.insert(id);
+                hashes.insert(hash);
+                report.imported += 1;
+            }
+            Err(e) => report.fail(index, e),
+        }
+    }
+
+    Ok(report)
+}
+
+// Tauri命令：导入前端导出的历史记录和偏好设置JSON，可重复执行，已导入的数据会被跳过
+#[tauri::command]
+pub async fn import_local_history(json: String) -> Result<HistoryImportReport, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    let data: Value = serde_json::from_str(&json).map_err(|e| format!("导入数据不是有效的JSON: {}", e))?;
+    let report = import_history(&db, data).await?;
+
+    // 全部失败时不记录导入时间，前端下次启动仍会尝试迁移
+    if report.imported > 0 || report.failed == 0 {
+        db.set_setting(LOCAL_HISTORY_IMPORTED_SETTING, &chrono::Utc::now().to_rfc3339())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization_records::init_optimization_record_tables;
+    use serde_json::json;
+
+    fn entry(id: &str, prompt: &str, optimized: &str) -> Value {
+        json!({
+            "id": id,
+            "originalPrompt": prompt,
+            "createdAt": "2024-05-01T08:00:00Z",
+            "results": [{ "modelName": "GLM4.5-Air", "optimizedPrompt": optimized }],
+            "favorite": true
+        })
+    }
+
+    #[test]
+    fn parse_history_array() {
+        let export = parse_export(json!([entry("a", "p", "o")])).unwrap();
+        assert_eq!(export.history.len(), 1);
+        assert!(export.preferences.is_empty());
+        assert_eq!(export.configs, 0);
+    }
+
+    #[test]
+    fn parse_storage_export() {
+        let export = parse_export(json!({
+            "history": [entry("a", "p", "o"), entry("b", "q", "r")],
+            "preferences": { "preferredModels": ["GLM4.5-Air"] },
+            "configs": [{ "apiKey": "secret" }],
+            "version": "1.0"
+        }))
+        .unwrap();
+        assert_eq!(export.history.len(), 2);
+        assert_eq!(export.preferences.len(), 1);
+        assert_eq!(export.configs, 1);
+
+        let empty = parse_export(json!({ "history": null })).unwrap();
+        assert!(empty.history.is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_shapes() {
+        assert!(parse_export(json!("history")).is_err());
+        assert!(parse_export(json!({ "history": {} })).is_err());
+        assert!(parse_export(json!({ "preferences": [] })).is_err());
+    }
+
+    #[test]
+    fn preference_keys_are_snake_case() {
+        assert_eq!(preference_setting_key("preferredModels"), "preference_preferred_models");
+        assert_eq!(preference_setting_key("theme"), "preference_theme");
+        assert_eq!(preference_setting_value(&json!("dark")), "dark");
+        assert_eq!(preference_setting_value(&json!(["a", 1])), r#"["a",1]"#);
+    }
+
+    #[tokio::test]
+    async fn duplicates_are_skipped_by_id_and_content() {
+        let db = DatabaseService::new("sqlite::memory:").await.unwrap();
+        init_optimization_record_tables(db.get_pool()).await.unwrap();
+
+        let report = import_history(&db, json!([entry("a", "提示词", "优化"), entry("b", "另一个", "结果")])).await.unwrap();
+        assert_eq!((report.imported, report.skipped, report.failed), (2, 0, 0));
+
+        // 相同ID，以及不同ID但内容相同的记录都跳过
+        let report = import_history(
+            &db,
+            json!([entry("a", "改过的", "内容"), entry("c", " 提示词 ", "优化"), entry("d", "", "x"), json!(42)]),
+        )
+        .await
+        .unwrap();
+        assert_eq!((report.imported, report.skipped, report.failed), (0, 2, 2));
+        assert_eq!(report.errors.len(), 2);
+
+        let favorites: i64 = sqlx::query("SELECT COUNT(*) AS count FROM optimization_requests WHERE favorite")
+            .fetch_one(db.get_pool())
+            .await
+            .unwrap()
+            .get("count");
+        assert_eq!(favorites, 2);
+    }
+}
@@ -0,0 +1,341 @@
+// 优化历史搜索 - 基于FTS5的全文检索，较短的词用LIKE补充，支持过滤条件与游标分页
+use serde::{Deserialize, Serialize};
+use sqlx::query::Query;
+use sqlx::sqlite::{SqliteArguments, SqliteRow};
+use sqlx::{Row, Sqlite};
+
+use crate::gesture_service::normalize_sql_timestamp;
+use crate::services::{optimization_history_from_row, OptimizationHistory};
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+// trigram分词下短于3个字符的词无法命中索引，改用LIKE匹配
+const MIN_FTS_TERM_CHARS: usize = 3;
+const SNIPPET_TOKENS: i32 = 16;
+
+// 按created_at、模型、任务和评分过滤，参数由HistoryFilters::bind按顺序绑定
+pub(crate) const HISTORY_FILTER: &str = r#"
+    (? IS NULL OR julianday(h.created_at) >= julianday(?))
+    AND (? IS NULL OR julianday(h.created_at) <= julianday(?))
+    AND (? IS NULL OR h.model = ?)
+    AND (? IS NULL OR h.task_id = ?)
+    AND (? IS NULL OR h.rating >= ?)
+"#;
+
+// 搜索与导出共用的过滤条件，均可选
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HistoryFilters {
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub model: Option<String>,
+    pub task_id: Option<i64>,
+    pub min_rating: Option<i32>,
+}
+
+impl HistoryFilters {
+    pub(crate) fn normalized(&self) -> Self {
+        Self {
+            start: self.start.as_deref().map(normalize_sql_timestamp),
+            end: self.end.as_deref().map(normalize_sql_timestamp),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn bind<'q>(&'q self, query: Query<'q, Sqlite, SqliteArguments<'q>>) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(&self.start)
+            .bind(&self.start)
+            .bind(&self.end)
+            .bind(&self.end)
+            .bind(&self.model)
+            .bind(&self.model)
+            .bind(self.task_id)
+            .bind(self.task_id)
+            .bind(self.min_rating)
+            .bind(self.min_rating)
+    }
+}
+
+// 搜索请求，text为空时按时间倒序列出
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HistorySearchQuery {
+    pub text: Option<String>,
+    #[serde(flatten)]
+    pub filters: HistoryFilters,
+    pub cursor: Option<String>, // 上一页返回的next_cursor
+    pub limit: Option<i64>,
+}
+
+// 单条搜索结果，snippet中命中的部分用<mark>标记
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistorySearchHit {
+    #[serde(flatten)]
+    pub history: OptimizationHistory,
+    pub original_snippet: Option<String>,
+    pub optimized_snippet: Option<String>,
+    pub score: Option<f64>, // bm25得分，越小越相关
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistorySearchPage {
+    pub items: Vec<HistorySearchHit>,
+    pub next_cursor: Option<String>,
+}
+
+// 游标：全文检索按 (得分, id) 分页，其余按 (创建时间, id) 分页
+#[derive(Debug, Clone, PartialEq)]
+enum SearchCursor {
+    Ranked { score: f64, id: i64 },
+    Recent { created_at: String, id: i64 },
+}
+
+impl SearchCursor {
+    fn encode(&self) -> String {
+        match self {
+            SearchCursor::Ranked { score, id } => format!("r:{:?}:{}", score, id),
+            SearchCursor::Recent { created_at, id } => format!("t:{}:{}", created_at, id),
+        }
+    }
+
+    fn decode(value: &str) -> Result<Self, String> {
+        let invalid = || format!("无效的分页游标: {}", value);
+        let (kind, rest) = value.split_once(':').ok_or_else(invalid)?;
+        let (key, id) = rest.rsplit_once(':').ok_or_else(invalid)?;
+        let id = id.parse().map_err(|_| invalid())?;
+        match kind {
+            "r" => Ok(SearchCursor::Ranked { score: key.parse().map_err(|_| invalid())?, id }),
+            "t" => Ok(SearchCursor::Recent { created_at: key.to_string(), id }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+// 将用户输入拆分为检索词，多个词之间为AND关系
+pub(crate) fn search_terms(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_string).collect()
+}
+
+// 每个词作为短语加引号，避免用户输入被解析为FTS5语法
+fn fts_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(crate) fn like_pattern(term: &str) -> String {
+    let escaped = term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+// 搜索与导出共用的查询条件：足够长的词走全文索引，较短的词用LIKE匹配，ids非空时只查询指定记录
+pub(crate) struct HistoryQuery {
+    filters: HistoryFilters,
+    fts: Option<String>,
+    patterns: Vec<String>,
+    ids: Vec<i64>,
+}
+
+impl HistoryQuery {
+    pub(crate) fn new(text: Option<&str>, filters: &HistoryFilters, ids: &[i64]) -> Self {
+        let (fts_terms, like_terms): (Vec<String>, Vec<String>) = text
+            .map(search_terms)
+            .unwrap_or_default()
+            .into_iter()
+            .partition(|term| term.chars().count() >= MIN_FTS_TERM_CHARS);
+
+        Self {
+            filters: filters.normalized(),
+            fts: (!fts_terms.is_empty()).then(|| fts_query(&fts_terms)),
+            patterns: like_terms.iter().map(|term| like_pattern(term)).collect(),
+            ids: ids.to_vec(),
+        }
+    }
+
+    // 有全文检索词时结果带bm25得分和摘要
+    pub(crate) fn ranked(&self) -> bool {
+        self.fts.is_some()
+    }
+
+    // 不含排序和分页的查询，调用方在外层按需要的游标分页
+    pub(crate) fn sql(&self) -> String {
+        let terms = " AND (h.original_prompt LIKE ? ESCAPE '\\' OR h.optimized_prompt LIKE ? ESCAPE '\\')".repeat(self.patterns.len());
+        let ids = if self.ids.is_empty() {
+            String::new()
+        } else {
+            format!(" AND h.id IN ({})", vec!["?"; self.ids.len()].join(", "))
+        };
+
+        if self.ranked() {
+            format!(
+                r#"
+                SELECT h.*, bm25(optimization_history_fts) AS score,
+                       snippet(optimization_history_fts, 0, '<mark>', '</mark>', '…', {tokens}) AS original_snippet,
+                       snippet(optimization_history_fts, 1, '<mark>', '</mark>', '…', {tokens}) AS optimized_snippet
+                FROM optimization_history_fts
+                JOIN optimization_history h ON h.id = optimization_history_fts.rowid
+                WHERE optimization_history_fts MATCH ? AND {filter}{terms}{ids}
+                "#,
+                tokens = SNIPPET_TOKENS,
+                filter = HISTORY_FILTER
+            )
+        } else {
+            format!("SELECT h.* FROM optimization_history h WHERE {filter}{terms}{ids}", filter = HISTORY_FILTER)
+        }
+    }
+
+    // 按sql()中占位符的顺序绑定参数
+    pub(crate) fn bind<'q>(&'q self, mut query: Query<'q, Sqlite, SqliteArguments<'q>>) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        if let Some(fts) = &self.fts {
+            query = query.bind(fts);
+        }
+        query = self.filters.bind(query);
+        for pattern in &self.patterns {
+            query = query.bind(pattern).bind(pattern);
+        }
+        for id in &self.ids {
+            query = query.bind(id);
+        }
+        query
+    }
+}
+
+fn hit_from_row(row: &SqliteRow, ranked: bool) -> HistorySearchHit {
+    HistorySearchHit {
+        history: optimization_history_from_row(row),
+        original_snippet: if ranked { row.get("original_snippet") } else { None },
+        optimized_snippet: if ranked { row.get("optimized_snippet") } else { None },
+        score: if ranked { row.get("score") } else { None },
+    }
+}
+
+// Tauri命令：搜索优化历史
+#[tauri::command]
+pub async fn search_optimization_history(query: HistorySearchQuery) -> Result<HistorySearchPage, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let cursor = query.cursor.as_deref().map(SearchCursor::decode).transpose()?;
+    let history_query = HistoryQuery::new(query.text.as_deref(), &query.filters, &[]);
+    let ranked = history_query.ranked();
+
+    // 多取一条用于判断是否还有下一页
+    let rows = if ranked {
+        let (cursor_score, cursor_id) = match &cursor {
+            Some(SearchCursor::Ranked { score, id }) => (Some(*score), Some(*id)),
+            Some(SearchCursor::Recent { .. }) => return Err("分页游标与搜索条件不匹配".to_string()),
+            None => (None, None),
+        };
+        let sql = format!(
+            r#"
+            SELECT * FROM ({base})
+            WHERE (? IS NULL OR score > ? OR (score = ? AND id > ?))
+            ORDER BY score, id
+            LIMIT ?
+            "#,
+            base = history_query.sql()
+        );
+        history_query.bind(sqlx::query(&sql))
+            .bind(cursor_score)
+            .bind(cursor_score)
+            .bind(cursor_score)
+            .bind(cursor_id)
+            .bind(limit + 1)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        let (cursor_created_at, cursor_id) = match &cursor {
+            Some(SearchCursor::Recent { created_at, id }) => (Some(created_at.clone()), Some(*id)),
+            Some(SearchCursor::Ranked { .. }) => return Err("分页游标与搜索条件不匹配".to_string()),
+            None => (None, None),
+        };
+        let sql = format!(
+            r#"
+            SELECT * FROM ({base})
+            WHERE (? IS NULL OR created_at < ? OR (created_at = ? AND id < ?))
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?
+            "#,
+            base = history_query.sql()
+        );
+        history_query.bind(sqlx::query(&sql))
+            .bind(&cursor_created_at)
+            .bind(&cursor_created_at)
+            .bind(&cursor_created_at)
+            .bind(cursor_id)
+            .bind(limit + 1)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?
+    };
+
+    let has_more = rows.len() as i64 > limit;
+    let rows = &rows[..rows.len().min(limit as usize)];
+    let next_cursor = rows.last().filter(|_| has_more).map(|row| {
+        let id: i64 = row.get("id");
+        if ranked {
+            SearchCursor::Ranked { score: row.get("score"), id }.encode()
+        } else {
+            SearchCursor::Recent { created_at: row.get("created_at"), id }.encode()
+        }
+    });
+
+    Ok(HistorySearchPage {
+        items: rows.iter().map(|row| hit_from_row(row, ranked)).collect(),
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursors = [
+            SearchCursor::Ranked { score: -1.25e-7, id: 42 },
+            SearchCursor::Ranked { score: 0.0, id: 1 },
+            SearchCursor::Recent { created_at: "2024-03-01 08:15:30.123+00:00".to_string(), id: 7 },
+        ];
+        for cursor in cursors {
+            assert_eq!(SearchCursor::decode(&cursor.encode()), Ok(cursor));
+        }
+    }
+
+    #[test]
+    fn invalid_cursor_is_rejected() {
+        for value in ["", "x:1:2", "r:abc:1", "t:2024-03-01", "r:1.0:id"] {
+            assert!(SearchCursor::decode(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn fts_query_quotes_terms() {
+        let terms = search_terms(r#"say "hi" OR  NEAR(a*)"#);
+        assert_eq!(fts_query(&terms), r#""say" """hi""" "OR" "NEAR(a*)""#);
+    }
+
+    #[test]
+    fn like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern(r"50%_a\b"), r"%50\%\_a\\b%");
+    }
+
+    #[test]
+    fn short_terms_fall_back_to_like() {
+        let query = HistoryQuery::new(Some("ab 数据库 x refactor"), &HistoryFilters::default(), &[]);
+        assert!(query.ranked());
+        assert_eq!(query.fts.as_deref(), Some(r#""数据库" "refactor""#));
+        assert_eq!(query.patterns, vec!["%ab%".to_string(), "%x%".to_string()]);
+
+        let query = HistoryQuery::new(Some("ab"), &HistoryFilters::default(), &[3, 4]);
+        assert!(!query.ranked());
+        assert_eq!(query.sql().matches('?').count(), 10 + 2 + 2);
+    }
+}
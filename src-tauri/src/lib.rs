@@ -1,20 +1,56 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Mutex;
+use tauri::Manager;
 use chrono::Utc;
 use uuid::Uuid;
 use once_cell::sync::Lazy;
 
 mod services;
 mod gesture_service;
+mod gesture_actions;
+mod gesture_smoothing;
+mod gesture_templates;
+mod gesture_keypoints;
+mod gesture_motion;
+mod gesture_dataset;
+mod gesture_calibration;
+mod gesture_stats;
+mod gesture_retention;
+mod tray;
+mod shortcuts;
+mod clipboard_optimizer;
+mod app_settings;
+mod history_search;
+mod optimization_records;
+mod history_import;
+mod history_export;
 use services::*;
 use gesture_service::*;
+use gesture_actions::*;
+use gesture_templates::*;
+use gesture_motion::*;
+use gesture_dataset::*;
+use gesture_calibration::*;
+use gesture_stats::*;
+use gesture_retention::*;
+use tray::init_tray;
+use shortcuts::*;
+use clipboard_optimizer::*;
+use app_settings::{apply_setting, apply_startup_settings, current_state};
+use history_search::*;
+use optimization_records::*;
+use history_import::*;
+use history_export::*;
 
 // 全局状态管理
 static PROCESS_MONITOR: Lazy<Mutex<ProcessMonitor>> = Lazy::new(|| {
     Mutex::new(ProcessMonitor::new())
 });
 
+// 应用数据目录中的数据库文件名
+const DATABASE_FILE: &str = "codingpal.db";
+
 static DATABASE: Lazy<Mutex<Option<DatabaseService>>> = Lazy::new(|| {
     Mutex::new(None)
 });
@@ -38,6 +74,7 @@ pub struct OptimizedPrompt {
     pub improvements: Vec<String>,
     pub confidence: f32,
     pub tokens_used: u32,
+    pub history_id: Option<i64>, // 保存到优化历史后的记录ID
 }
 
 // Tauri命令函数
@@ -88,7 +125,13 @@ async fn optimize_prompt_with_config(
     prompt: String,
     _config: OptimizationConfig,
 ) -> Result<OptimizedPrompt, String> {
+    run_prompt_optimization(prompt).await
+}
+
+// 调用GLM优化提示词并保存历史，供命令和手势动作共用
+pub(crate) async fn run_prompt_optimization(prompt: String) -> Result<OptimizedPrompt, String> {
     let start_time = std::time::Instant::now();
+    let _activity = OptimizationActivity::begin();
     
     let client = {
         let glm_client_guard = GLM_CLIENT.lock().map_err(|e| e.to_string())?;
@@ -102,13 +145,14 @@ async fn optimize_prompt_with_config(
     
     let processing_time = start_time.elapsed().as_millis() as i32;
     
-    let result = OptimizedPrompt {
+    let mut result = OptimizedPrompt {
         id: Uuid::new_v4().to_string(),
         original: prompt.clone(),
         optimized: optimized_text,
         improvements,
         confidence: 0.85,
         tokens_used,
+        history_id: None,
     };
     
     // 保存优化历史到数据库
@@ -125,9 +169,13 @@ async fn optimize_prompt_with_config(
             confidence: result.confidence,
             tokens_used: tokens_used as i32,
             processing_time_ms: processing_time,
+            task_id: db.get_active_task_id().await.ok().flatten(),
+            model: Some(client.model().to_string()),
+            rating: None,
+            improvements: result.improvements.clone(),
             created_at: Utc::now(),
         };
-        let _ = db.save_optimization_history(&history).await;
+        result.history_id = db.save_optimization_history(&history).await.ok();
     }
     
     Ok(result)
@@ -137,16 +185,251 @@ async fn optimize_prompt_with_config(
 async fn create_task_folder(folder_name: String) -> Result<String, String> {
     use std::fs;
     
-    let base_path = "CodingPal/tasks";
-    let folder_path = format!("{}/{}", base_path, folder_name);
+    if folder_name.trim().is_empty() || folder_name.contains(['/', '\\']) || folder_name.starts_with('.') {
+        return Err("无效的任务文件夹名称".to_string());
+    }
+    
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    let base_path = resolve_task_root(&db).await.map_err(|e| e.to_string())?;
+    let folder_path = base_path.join(&folder_name);
     
     if let Err(e) = fs::create_dir_all(&folder_path) {
         return Err(format!("创建文件夹失败: {}", e));
     }
     
+    // 创建任务提示词文件，供IDE中直接编辑
+    let prompt_path = folder_path.join(TASK_PROMPT_FILE);
+    if !prompt_path.exists() {
+        fs::write(&prompt_path, "").map_err(|e| format!("创建提示词文件失败: {}", e))?;
+    }
+    
+    let folder_path = folder_path.to_string_lossy().to_string();
+    db.ensure_task_folder(&folder_name, &folder_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    
     Ok(folder_path)
 }
 
+#[tauri::command]
+async fn get_task_folders() -> Result<Vec<TaskFolder>, String> {
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    db.get_task_folders()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskWorktreeStats {
+    pub task_id: i64,
+    pub folder_name: String,
+    pub folder_path: String,
+    pub branch: String,
+    pub stats: Option<WorktreeDiffStats>,
+    pub error: Option<String>,
+}
+
+#[tauri::command]
+async fn create_task_worktree(
+    folder_name: String,
+    repo_path: String,
+    branch: Option<String>,
+) -> Result<TaskFolder, String> {
+    if folder_name.trim().is_empty() || folder_name.contains(['/', '\\']) || folder_name.starts_with('.') {
+        return Err("无效的任务文件夹名称".to_string());
+    }
+    
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    let repo_path = repo_toplevel(std::path::Path::new(&repo_path))
+        .await
+        .map_err(|e| format!("不是有效的git仓库: {}", e))?;
+    let branch = branch
+        .filter(|b| !b.trim().is_empty())
+        .unwrap_or_else(|| default_task_branch(&folder_name));
+    
+    let base_path = resolve_task_root(&db).await.map_err(|e| e.to_string())?;
+    let folder_path = base_path.join(&folder_name);
+    if folder_path.exists() {
+        return Err(format!("任务文件夹已存在: {}", folder_path.display()));
+    }
+    
+    create_worktree(std::path::Path::new(&repo_path), &folder_path, &branch)
+        .await
+        .map_err(|e| format!("创建工作树失败: {}", e))?;
+    
+    let folder_path = folder_path.to_string_lossy().to_string();
+    let task_id = db.ensure_task_folder(&folder_name, &folder_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    db.set_task_worktree(task_id, &repo_path, &branch)
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    db.get_task_folder(task_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "任务不存在".to_string())
+}
+
+#[tauri::command]
+async fn get_task_worktree_stats() -> Result<Vec<TaskWorktreeStats>, String> {
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    let tasks = db.get_task_folders().await.map_err(|e| e.to_string())?;
+    let mut results = Vec::new();
+    
+    for task in tasks {
+        let Some(branch) = task.branch.clone() else { continue };
+        if task.status == TASK_STATUS_ARCHIVED {
+            continue;
+        }
+        
+        // 单个工作树出错不影响其他任务的统计
+        let (stats, error) = match worktree_diff_stats(std::path::Path::new(&task.folder_path), &branch).await {
+            Ok(stats) => (Some(stats), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        
+        results.push(TaskWorktreeStats {
+            task_id: task.id,
+            folder_name: task.folder_name,
+            folder_path: task.folder_path,
+            branch,
+            stats,
+            error,
+        });
+    }
+    
+    Ok(results)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskArchiveResult {
+    pub task: TaskFolder,
+    pub bundle_path: String,
+}
+
+#[tauri::command]
+async fn archive_task(task_id: i64, force: Option<bool>) -> Result<TaskArchiveResult, String> {
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    let task = db.get_task_folder(task_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "任务不存在".to_string())?;
+    
+    // 先标记归档，避免文件夹监听把移除的工作树当作外部删除，同时让任务包记录到归档状态
+    db.update_task_status(task.id, TASK_STATUS_ARCHIVED)
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    let archive_dir = resolve_archive_dir(&db).await.map_err(|e| e.to_string())?;
+    let bundle_path = match export_task_bundle(&db, task.id, &archive_dir).await {
+        Ok(path) => path,
+        Err(e) => {
+            let _ = db.update_task_status(task.id, &task.status).await;
+            return Err(format!("打包任务失败: {}", e));
+        }
+    };
+    
+    if let Some(repo_path) = &task.repo_path {
+        let folder_path = std::path::Path::new(&task.folder_path);
+        if folder_path.exists() {
+            if let Err(e) = remove_worktree(std::path::Path::new(repo_path), folder_path, force.unwrap_or(false)).await {
+                let _ = db.update_task_status(task.id, &task.status).await;
+                return Err(format!("移除工作树失败: {}", e));
+            }
+        }
+    }
+    
+    let task = db.get_task_folder(task.id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "任务不存在".to_string())?;
+    
+    Ok(TaskArchiveResult {
+        task,
+        bundle_path: bundle_path.to_string_lossy().to_string(),
+    })
+}
+
+#[tauri::command]
+async fn export_task(task_id: i64) -> Result<String, String> {
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    let archive_dir = resolve_archive_dir(&db).await.map_err(|e| e.to_string())?;
+    let bundle_path = export_task_bundle(&db, task_id, &archive_dir)
+        .await
+        .map_err(|e| format!("打包任务失败: {}", e))?;
+    
+    Ok(bundle_path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn import_task(bundle_path: String) -> Result<TaskFolder, String> {
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    let task_root = resolve_task_root(&db).await.map_err(|e| e.to_string())?;
+    let task_id = import_task_bundle(&db, std::path::Path::new(&bundle_path), &task_root)
+        .await
+        .map_err(|e| format!("导入任务包失败: {}", e))?;
+    
+    db.get_task_folder(task_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "任务不存在".to_string())
+}
+
+#[tauri::command]
+async fn get_active_task() -> Result<Option<TaskFolder>, String> {
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    match db.get_active_task_id().await.map_err(|e| e.to_string())? {
+        Some(task_id) => db.get_task_folder(task_id).await.map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+async fn set_active_task(task_id: Option<i64>) -> Result<(), String> {
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    let value = task_id.map(|id| id.to_string()).unwrap_or_default();
+    db.set_setting("active_task_id", &value)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn get_optimization_history(limit: i32) -> Result<Vec<OptimizationHistory>, String> {
     let db = {
@@ -159,6 +442,22 @@ async fn get_optimization_history(limit: i32) -> Result<Vec<OptimizationHistory>
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn rate_optimization(history_id: i64, rating: Option<i32>) -> Result<bool, String> {
+    if rating.is_some_and(|r| !(1..=5).contains(&r)) {
+        return Err("评分需要在1到5之间".to_string());
+    }
+    
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    db.set_optimization_rating(history_id, rating)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn get_setting(key: String) -> Result<Option<String>, String> {
     let db = {
@@ -172,13 +471,75 @@ async fn get_setting(key: String) -> Result<Option<String>, String> {
 }
 
 #[tauri::command]
-async fn set_setting(key: String, value: String) -> Result<(), String> {
+async fn set_setting(app: tauri::AppHandle, key: String, value: String) -> Result<(), String> {
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    // 先应用副作用（窗口置顶、开机启动等），失败时不保存
+    let previous = current_state(&app, &key)?;
+    apply_setting(&app, &key, &value)?;
+    
+    // 保存失败时恢复原来的状态，避免设置与实际状态不一致
+    if let Err(e) = db.set_setting(&key, &value).await {
+        if let Some(previous) = previous {
+            if let Err(rollback) = apply_setting(&app, &key, &previous.to_string()) {
+                eprintln!("恢复设置{}失败: {}", key, rollback);
+            }
+        }
+        return Err(e.to_string());
+    }
+    Ok(())
+}
+
+// 开机启动项的实际状态，可能与设置不一致（例如用户手动删除了启动项）
+#[tauri::command]
+async fn get_auto_start_enabled(app: tauri::AppHandle) -> Result<bool, String> {
+    is_auto_start_enabled(&app).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_companion_state() -> Result<CompanionState, String> {
+    let companion = COMPANION.lock().map_err(|e| e.to_string())?;
+    Ok(companion.state())
+}
+
+#[tauri::command]
+async fn get_break_status() -> Result<BreakStatus, String> {
+    let scheduler = BREAK_SCHEDULER.lock().map_err(|e| e.to_string())?;
+    Ok(scheduler.status())
+}
+
+#[tauri::command]
+async fn skip_break(app: tauri::AppHandle) -> Result<bool, String> {
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    
+    let event = {
+        let mut scheduler = BREAK_SCHEDULER.lock().map_err(|e| e.to_string())?;
+        scheduler.skip()
+    };
+    
+    match event {
+        Some(event) => {
+            handle_break_event(&app, &db, &event).await;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[tauri::command]
+async fn get_break_logs(limit: i32) -> Result<Vec<BreakLog>, String> {
     let db = {
         let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
         db_guard.as_ref().ok_or("数据库未初始化")?.clone()
     };
     
-    db.set_setting(&key, &value)
+    db.get_break_logs(limit)
         .await
         .map_err(|e| e.to_string())
 }
@@ -205,36 +566,169 @@ pub fn run() {
         .plugin(tauri_plugin_window_state::Builder::default().build())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .invoke_handler(tauri::generate_handler![
             get_ide_processes,
             initialize_glm_client,
             optimize_prompt_with_config,
             create_task_folder,
+            get_task_folders,
+            create_task_worktree,
+            get_task_worktree_stats,
+            archive_task,
+            export_task,
+            import_task,
+            get_active_task,
+            set_active_task,
             get_optimization_history,
+            search_optimization_history,
+            rate_optimization,
+            save_optimization_record,
+            get_optimization_record,
+            get_optimization_records,
+            query_optimization_records,
+            delete_optimization_records,
+            clear_optimization_records,
+            update_optimization_result_rating,
+            select_optimization_result,
+            get_optimization_record_stats,
+            import_local_history,
+            export_optimization_history,
             get_setting,
             set_setting,
             get_process_stats,
+            get_auto_start_enabled,
+            get_companion_state,
+            get_break_status,
+            skip_break,
+            get_break_logs,
             get_gesture_configs,
             update_gesture_config,
+            create_gesture_config,
+            delete_gesture_config,
             save_gesture_record,
+            end_gesture_session,
             get_gesture_history,
+            correct_gesture_record,
+            get_record_keypoints,
+            get_keypoint_records,
+            export_gesture_dataset,
+            start_gesture_calibration,
+            get_gesture_calibration_status,
+            add_gesture_calibration_sample,
+            propose_gesture_thresholds,
+            apply_gesture_thresholds,
+            cancel_gesture_calibration,
             get_gesture_stats,
-            clear_gesture_history
+            get_gesture_time_buckets,
+            get_gesture_confidence_stats,
+            get_gesture_confusion_matrix,
+            clear_gesture_history,
+            get_gesture_retention_policy,
+            set_gesture_retention_policy,
+            run_gesture_retention,
+            delete_gesture_records,
+            delete_gesture_records_by_type,
+            delete_gesture_records_in_range,
+            classify_keypoints,
+            classify_keypoint_sequence,
+            enroll_gesture_samples,
+            capture_gesture_sample,
+            get_gesture_templates,
+            delete_gesture_template,
+            get_gesture_bindings,
+            save_gesture_binding,
+            delete_gesture_binding,
+            get_gesture_action_logs,
+            set_monitoring_paused,
+            get_global_shortcuts,
+            set_global_shortcut,
+            reset_global_shortcuts,
+            optimize_clipboard,
+            undo_clipboard_optimization
         ])
-        .setup(|_app| {
-            // 初始化数据库
-            tauri::async_runtime::spawn(async {
-                let database_url = "sqlite::memory:";
-                match DatabaseService::new(database_url).await {
+        .setup(|app| {
+            let app_handle = app.handle().clone();
+            
+            // 启动桌面伙伴状态机
+            start_companion(app_handle.clone());
+            
+            // 创建系统托盘
+            if let Err(e) = init_tray(&app_handle) {
+                eprintln!("创建系统托盘失败: {}", e);
+            }
+            
+            // 初始化数据库，保存在应用数据目录中，重启后历史记录和设置仍然保留
+            let database_path = app.path().app_data_dir().map(|dir| dir.join(DATABASE_FILE));
+            tauri::async_runtime::spawn(async move {
+                // 无法使用数据目录时退回内存数据库，本次运行的数据不会保留
+                let database_path = database_path.map_err(anyhow::Error::from).and_then(|path| {
+                    if let Some(dir) = path.parent() {
+                        std::fs::create_dir_all(dir)?;
+                    }
+                    Ok(path)
+                });
+                let opened = match database_path {
+                    Ok(path) => DatabaseService::open(&path).await,
+                    Err(e) => {
+                        eprintln!("无法创建数据库文件，使用内存数据库: {}", e);
+                        DatabaseService::new("sqlite::memory:").await
+                    }
+                };
+                match opened {
                     Ok(db) => {
                         // 初始化手势识别表
                         if let Err(e) = init_gesture_tables(db.get_pool()).await {
                             eprintln!("初始化手势识别表失败: {}", e);
                         }
+                        if let Err(e) = init_gesture_action_tables(db.get_pool()).await {
+                            eprintln!("初始化手势动作表失败: {}", e);
+                        }
+                        if let Err(e) = init_gesture_template_tables(db.get_pool()).await {
+                            eprintln!("初始化手势模板表失败: {}", e);
+                        }
+                        if let Err(e) = init_shortcut_tables(db.get_pool()).await {
+                            eprintln!("初始化快捷键表失败: {}", e);
+                        }
+                        if let Err(e) = init_optimization_record_tables(db.get_pool()).await {
+                            eprintln!("初始化优化记录表失败: {}", e);
+                        }
+                        if let Err(e) = load_gesture_recognition_state(&db).await {
+                            eprintln!("读取手势识别开关失败: {}", e);
+                        }
                         
-                        let mut db_guard = DATABASE.lock().unwrap();
-                        *db_guard = Some(db);
+                        {
+                            let mut db_guard = DATABASE.lock().unwrap();
+                            *db_guard = Some(db.clone());
+                        }
                         println!("数据库初始化成功");
+                        
+                        // 按实际状态同步窗口置顶与开机启动设置
+                        apply_startup_settings(&app_handle, &db).await;
+                        
+                        // 按保留策略定期清理手势数据
+                        start_retention_job(db.clone());
+                        
+                        // 结束手已离开画面的手势
+                        start_gesture_session_sweeper(app_handle.clone(), db.clone());
+                        
+                        // 按IDE活动提醒休息
+                        start_break_reminders(app_handle.clone(), db.clone());
+                        
+                        // 注册全局快捷键
+                        if let Err(e) = register_global_shortcuts(&app_handle, db.get_pool()).await {
+                            eprintln!("注册全局快捷键失败: {}", e);
+                        }
+                        
+                        // 监听任务根目录的变化
+                        match resolve_task_root(&db).await {
+                            Ok(root) => {
+                                if let Err(e) = start_task_watcher(app_handle, db, root) {
+                                    eprintln!("启动任务文件夹监听失败: {}", e);
+                                }
+                            }
+                            Err(e) => eprintln!("解析任务根目录失败: {}", e),
+                        }
                     }
                     Err(e) => {
                         eprintln!("数据库初始化失败: {}", e);
@@ -0,0 +1,783 @@
+// 多模型优化记录 - 一次优化请求对应多个模型的结果，替代前端localStorage中的历史记录
+// 结构体字段使用camelCase，与前端OptimizationRecord/ModelResult保持一致
+// 成功的结果同时写入optimization_history，使历史搜索和导出也能查到这些记录
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnection;
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::gesture_service::normalize_sql_timestamp;
+use crate::history_search::like_pattern;
+use crate::services::{insert_optimization_history, OptimizationHistory};
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+
+// 单个模型的优化结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelResult {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub record_id: String,
+    pub model_name: String,
+    #[serde(default)]
+    pub optimized_prompt: String,
+    #[serde(default)]
+    pub tokens_used: i64,
+    #[serde(default)]
+    pub response_time: i64, // 毫秒
+    #[serde(default = "default_result_status")]
+    pub status: String, // success / error / pending
+    pub user_rating: Option<i32>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub selected: bool, // 用户最终采用的结果
+}
+
+fn default_result_status() -> String {
+    "success".to_string()
+}
+
+// 一次优化请求
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizationRecord {
+    #[serde(default)]
+    pub id: String,
+    pub original_prompt: String,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub user_language: String,
+    #[serde(default)]
+    pub selected_models: Vec<String>,
+    #[serde(default)]
+    pub results: Vec<ModelResult>,
+    #[serde(default)]
+    pub task_id: Option<i64>,
+    #[serde(default)]
+    pub favorite: bool, // 收藏标记，对应前端PromptHistory.favorite
+}
+
+// 分页结果，与history-service.ts的getPaginatedRecords一致
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizationRecordPage {
+    pub records: Vec<OptimizationRecord>,
+    pub current_page: i64,
+    pub total_pages: i64,
+    pub total_records: i64,
+    pub has_next_page: bool,
+    pub has_prev_page: bool,
+}
+
+// 查询条件，均可选，对应searchRecords/getRecordsByDateRange/getRecordsByModel
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizationRecordFilter {
+    pub text: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub model_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizationRecordStats {
+    pub total_records: i64,
+    pub total_optimizations: i64,
+    pub model_usage: HashMap<String, i64>,
+    pub average_response_time: i64,
+    pub success_rate: i64, // 百分比
+}
+
+// 初始化优化请求表和模型结果表
+pub async fn init_optimization_record_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS optimization_requests (
+            id VARCHAR(64) PRIMARY KEY,
+            original_prompt TEXT NOT NULL,
+            user_language VARCHAR(20),
+            selected_models TEXT NOT NULL DEFAULT '[]',
+            task_id INTEGER,
+            content_hash VARCHAR(16) NOT NULL DEFAULT '',
+            favorite BOOLEAN NOT NULL DEFAULT false,
+            created_at TIMESTAMP NOT NULL,
+            FOREIGN KEY (task_id) REFERENCES task_folders(id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS optimization_results (
+            id VARCHAR(64) PRIMARY KEY,
+            request_id VARCHAR(64) NOT NULL,
+            model_name VARCHAR(50) NOT NULL,
+            optimized_prompt TEXT NOT NULL DEFAULT '',
+            tokens_used INTEGER DEFAULT 0,
+            response_time_ms INTEGER DEFAULT 0,
+            status VARCHAR(20) NOT NULL DEFAULT 'success',
+            error TEXT,
+            rating INTEGER,
+            selected BOOLEAN DEFAULT false,
+            position INTEGER NOT NULL DEFAULT 0,
+            history_id INTEGER, -- 对应的optimization_history记录，只有成功的结果才有
+            FOREIGN KEY (request_id) REFERENCES optimization_requests(id),
+            FOREIGN KEY (history_id) REFERENCES optimization_history(id) ON DELETE SET NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_optimization_requests_hash ON optimization_requests(content_hash)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_optimization_results_request ON optimization_results(request_id)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+fn record_from_row(row: &sqlx::sqlite::SqliteRow) -> OptimizationRecord {
+    let selected_models: String = row.get("selected_models");
+    OptimizationRecord {
+        id: row.get("id"),
+        original_prompt: row.get("original_prompt"),
+        created_at: row.get("created_at"),
+        user_language: row.get::<Option<String>, _>("user_language").unwrap_or_default(),
+        selected_models: serde_json::from_str(&selected_models).unwrap_or_default(),
+        results: Vec::new(),
+        task_id: row.get("task_id"),
+        favorite: row.get("favorite"),
+    }
+}
+
+fn result_from_row(row: &sqlx::sqlite::SqliteRow) -> ModelResult {
+    ModelResult {
+        id: row.get("id"),
+        record_id: row.get("request_id"),
+        model_name: row.get("model_name"),
+        optimized_prompt: row.get("optimized_prompt"),
+        tokens_used: row.get("tokens_used"),
+        response_time: row.get("response_time_ms"),
+        status: row.get("status"),
+        user_rating: row.get("rating"),
+        error: row.get("error"),
+        selected: row.get("selected"),
+    }
+}
+
+// 为记录加载各模型结果
+async fn attach_results(pool: &SqlitePool, records: &mut [OptimizationRecord]) -> Result<(), sqlx::Error> {
+    if records.is_empty() {
+        return Ok(());
+    }
+
+    let placeholders = vec!["?"; records.len()].join(", ");
+    let sql = format!(
+        "SELECT * FROM optimization_results WHERE request_id IN ({}) ORDER BY position, id",
+        placeholders
+    );
+    let mut query = sqlx::query(&sql);
+    for record in records.iter() {
+        query = query.bind(&record.id);
+    }
+
+    let mut results: HashMap<String, Vec<ModelResult>> = HashMap::new();
+    for row in query.fetch_all(pool).await? {
+        let result = result_from_row(&row);
+        results.entry(result.record_id.clone()).or_default().push(result);
+    }
+    for record in records.iter_mut() {
+        record.results = results.remove(&record.id).unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+// 记录内容的哈希（FNV-1a），由原始提示词和各模型结果计算，不含ID和时间，用于导入时去重
+pub fn content_hash(record: &OptimizationRecord) -> String {
+    let mut results: Vec<(&str, &str)> = record
+        .results
+        .iter()
+        .map(|r| (r.model_name.as_str(), r.optimized_prompt.trim()))
+        .collect();
+    results.sort_unstable();
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes.iter().chain([0u8].iter()) {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    feed(record.original_prompt.trim().as_bytes());
+    for (model, prompt) in results {
+        feed(model.as_bytes());
+        feed(prompt.as_bytes());
+    }
+    format!("{:016x}", hash)
+}
+
+// 成功的结果对应的优化历史，供历史搜索和导出使用
+fn history_entry(record: &OptimizationRecord, result: &ModelResult) -> Option<OptimizationHistory> {
+    if result.status != "success" || result.optimized_prompt.trim().is_empty() {
+        return None;
+    }
+    Some(OptimizationHistory {
+        id: 0,
+        original_prompt: record.original_prompt.clone(),
+        optimized_prompt: result.optimized_prompt.clone(),
+        confidence: 0.0,
+        tokens_used: result.tokens_used as i32,
+        processing_time_ms: result.response_time as i32,
+        task_id: record.task_id,
+        model: Some(result.model_name.clone()),
+        rating: result.user_rating,
+        improvements: Vec::new(),
+        created_at: record.created_at,
+    })
+}
+
+// 删除记录的全部结果及其对应的优化历史，返回删除的结果数
+async fn delete_results(conn: &mut SqliteConnection, request_ids: &[String]) -> Result<u64, sqlx::Error> {
+    let placeholders = vec!["?"; request_ids.len()].join(", ");
+    let history_sql = format!(
+        "DELETE FROM optimization_history WHERE id IN (SELECT history_id FROM optimization_results WHERE request_id IN ({}))",
+        placeholders
+    );
+    let results_sql = format!("DELETE FROM optimization_results WHERE request_id IN ({})", placeholders);
+
+    let mut deleted = 0;
+    for sql in [history_sql, results_sql] {
+        let mut query = sqlx::query(&sql);
+        for id in request_ids {
+            query = query.bind(id);
+        }
+        deleted = query.execute(&mut *conn).await?.rows_affected();
+    }
+    Ok(deleted)
+}
+
+// 保存一条记录及其全部结果，ID已存在时整体替换
+pub async fn save_record(pool: &SqlitePool, mut record: OptimizationRecord) -> anyhow::Result<String> {
+    if record.id.trim().is_empty() {
+        record.id = Uuid::new_v4().to_string();
+    }
+
+    let mut tx = pool.begin().await?;
+
+    // 结果ID已属于其他记录时拒绝保存，避免替换时把其他记录的结果移过来
+    let result_ids: Vec<&str> = record.results.iter().map(|r| r.id.trim()).filter(|id| !id.is_empty()).collect();
+    if !result_ids.is_empty() {
+        let sql = format!(
+            "SELECT id FROM optimization_results WHERE id IN ({}) AND request_id != ? LIMIT 1",
+            vec!["?"; result_ids.len()].join(", ")
+        );
+        let mut query = sqlx::query(&sql);
+        for id in &result_ids {
+            query = query.bind(*id);
+        }
+        if let Some(row) = query.bind(&record.id).fetch_optional(&mut *tx).await? {
+            anyhow::bail!("结果{}属于其他记录", row.get::<String, _>("id"));
+        }
+    }
+
+    delete_results(&mut tx, std::slice::from_ref(&record.id)).await?;
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO optimization_requests (id, original_prompt, user_language, selected_models, task_id, content_hash, favorite, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&record.id)
+    .bind(&record.original_prompt)
+    .bind(&record.user_language)
+    .bind(serde_json::to_string(&record.selected_models).unwrap_or_else(|_| "[]".to_string()))
+    .bind(record.task_id)
+    .bind(content_hash(&record))
+    .bind(record.favorite)
+    .bind(record.created_at)
+    .execute(&mut *tx)
+    .await?;
+
+    for (position, result) in record.results.iter().enumerate() {
+        let id = if result.id.trim().is_empty() { Uuid::new_v4().to_string() } else { result.id.clone() };
+        let history_id = match history_entry(&record, result) {
+            Some(history) => Some(insert_optimization_history(&mut tx, &history).await?),
+            None => None,
+        };
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO optimization_results
+            (id, request_id, model_name, optimized_prompt, tokens_used, response_time_ms, status, error, rating, selected, position, history_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(&record.id)
+        .bind(&result.model_name)
+        .bind(&result.optimized_prompt)
+        .bind(result.tokens_used)
+        .bind(result.response_time)
+        .bind(&result.status)
+        .bind(&result.error)
+        .bind(result.user_rating)
+        .bind(result.selected)
+        .bind(position as i64)
+        .bind(history_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(record.id)
+}
+
+// 按条件查询记录，按创建时间倒序
+pub async fn query_records(pool: &SqlitePool, filter: &OptimizationRecordFilter) -> Result<Vec<OptimizationRecord>, sqlx::Error> {
+    let text = filter.text.as_deref().map(str::trim).filter(|t| !t.is_empty()).map(like_pattern);
+    let start = filter.start.as_deref().map(normalize_sql_timestamp);
+    let end = filter.end.as_deref().map(normalize_sql_timestamp);
+
+    let rows = sqlx::query(
+        r#"
+        SELECT r.* FROM optimization_requests r
+        WHERE (? IS NULL OR julianday(r.created_at) >= julianday(?))
+          AND (? IS NULL OR julianday(r.created_at) <= julianday(?))
+          AND (? IS NULL
+               OR EXISTS (SELECT 1 FROM json_each(r.selected_models) WHERE value = ?)
+               OR EXISTS (SELECT 1 FROM optimization_results s WHERE s.request_id = r.id AND s.model_name = ?))
+          AND (? IS NULL
+               OR r.original_prompt LIKE ? ESCAPE '\'
+               OR EXISTS (SELECT 1 FROM optimization_results s WHERE s.request_id = r.id
+                          AND (s.optimized_prompt LIKE ? ESCAPE '\' OR s.model_name LIKE ? ESCAPE '\')))
+        ORDER BY r.created_at DESC, r.id
+        "#,
+    )
+    .bind(&start)
+    .bind(&start)
+    .bind(&end)
+    .bind(&end)
+    .bind(&filter.model_name)
+    .bind(&filter.model_name)
+    .bind(&filter.model_name)
+    .bind(&text)
+    .bind(&text)
+    .bind(&text)
+    .bind(&text)
+    .fetch_all(pool)
+    .await?;
+
+    let mut records: Vec<OptimizationRecord> = rows.iter().map(record_from_row).collect();
+    attach_results(pool, &mut records).await?;
+    Ok(records)
+}
+
+// Tauri命令：保存优化记录，返回记录ID
+#[tauri::command]
+pub async fn save_optimization_record(record: OptimizationRecord) -> Result<String, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    save_record(db.get_pool(), record).await.map_err(|e| e.to_string())
+}
+
+// Tauri命令：获取单条记录
+#[tauri::command]
+pub async fn get_optimization_record(id: String) -> Result<Option<OptimizationRecord>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+
+    let row = sqlx::query("SELECT * FROM optimization_requests WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut records: Vec<OptimizationRecord> = row.iter().map(record_from_row).collect();
+    attach_results(pool, &mut records).await.map_err(|e| e.to_string())?;
+    Ok(records.pop())
+}
+
+// Tauri命令：分页获取记录，page从1开始
+#[tauri::command]
+pub async fn get_optimization_records(page: Option<i64>, page_size: Option<i64>) -> Result<OptimizationRecordPage, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+    let page = page.unwrap_or(1).max(1);
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+
+    let total_records: i64 = sqlx::query("SELECT COUNT(*) AS count FROM optimization_requests")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .get("count");
+
+    let rows = sqlx::query("SELECT * FROM optimization_requests ORDER BY created_at DESC, id LIMIT ? OFFSET ?")
+        .bind(page_size)
+        .bind((page - 1) * page_size)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut records: Vec<OptimizationRecord> = rows.iter().map(record_from_row).collect();
+    attach_results(pool, &mut records).await.map_err(|e| e.to_string())?;
+
+    let total_pages = (total_records + page_size - 1) / page_size;
+    Ok(OptimizationRecordPage {
+        records,
+        current_page: page,
+        total_pages,
+        total_records,
+        has_next_page: page < total_pages,
+        has_prev_page: page > 1,
+    })
+}
+
+// Tauri命令：按关键词、日期范围和模型查询记录
+#[tauri::command]
+pub async fn query_optimization_records(filter: OptimizationRecordFilter) -> Result<Vec<OptimizationRecord>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    query_records(db.get_pool(), &filter).await.map_err(|e| e.to_string())
+}
+
+// Tauri命令：删除记录及其结果，返回删除的记录数
+#[tauri::command]
+pub async fn delete_optimization_records(ids: Vec<String>) -> Result<u64, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let mut tx = db.get_pool().begin().await.map_err(|e| e.to_string())?;
+    delete_results(&mut tx, &ids).await.map_err(|e| e.to_string())?;
+    let sql = format!("DELETE FROM optimization_requests WHERE id IN ({})", vec!["?"; ids.len()].join(", "));
+    let mut query = sqlx::query(&sql);
+    for id in &ids {
+        query = query.bind(id);
+    }
+    let deleted = query.execute(&mut *tx).await.map_err(|e| e.to_string())?.rows_affected();
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(deleted)
+}
+
+// Tauri命令：清空全部记录
+#[tauri::command]
+pub async fn clear_optimization_records() -> Result<bool, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let mut tx = db.get_pool().begin().await.map_err(|e| e.to_string())?;
+
+    sqlx::query("DELETE FROM optimization_history WHERE id IN (SELECT history_id FROM optimization_results)")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM optimization_results")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let result = sqlx::query("DELETE FROM optimization_requests")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Tauri命令：更新某个结果的评分，rating为空时清除评分
+#[tauri::command]
+pub async fn update_optimization_result_rating(
+    record_id: String,
+    result_id: String,
+    rating: Option<i32>,
+) -> Result<(), String> {
+    use crate::DATABASE;
+    if rating.is_some_and(|r| !(1..=5).contains(&r)) {
+        return Err("评分需要在1到5之间".to_string());
+    }
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    // 对应的优化历史同步更新评分，按评分过滤搜索和导出时保持一致
+    let mut tx = db.get_pool().begin().await.map_err(|e| e.to_string())?;
+    let result = sqlx::query("UPDATE optimization_results SET rating = ? WHERE id = ? AND request_id = ?")
+        .bind(rating)
+        .bind(&result_id)
+        .bind(&record_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if result.rows_affected() == 0 {
+        return Err("结果不存在".to_string());
+    }
+    sqlx::query(
+        "UPDATE optimization_history SET rating = ? WHERE id = (SELECT history_id FROM optimization_results WHERE id = ? AND request_id = ?)"
+    )
+    .bind(rating)
+    .bind(&result_id)
+    .bind(&record_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// Tauri命令：标记用户采用的结果，同一记录中只有一个结果被选中
+#[tauri::command]
+pub async fn select_optimization_result(record_id: String, result_id: Option<String>) -> Result<(), String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    let result = sqlx::query("UPDATE optimization_results SET selected = (id IS ?) WHERE request_id = ?")
+        .bind(&result_id)
+        .bind(&record_id)
+        .execute(db.get_pool())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if result.rows_affected() == 0 {
+        return Err("记录不存在".to_string());
+    }
+    Ok(())
+}
+
+// Tauri命令：统计信息，与history-service.ts的getStatistics一致
+#[tauri::command]
+pub async fn get_optimization_record_stats() -> Result<OptimizationRecordStats, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+
+    let total_records: i64 = sqlx::query("SELECT COUNT(*) AS count FROM optimization_requests")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .get("count");
+
+    let totals = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS total, AVG(response_time_ms) AS avg_time,
+               SUM(error IS NULL OR error = '') AS successful
+        FROM optimization_results
+        "#,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let total_optimizations: i64 = totals.get("total");
+    let successful: Option<i64> = totals.get("successful");
+
+    let model_usage = sqlx::query("SELECT model_name, COUNT(*) AS count FROM optimization_results GROUP BY model_name")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|row| (row.get("model_name"), row.get("count")))
+        .collect();
+
+    let success_rate = if total_optimizations > 0 {
+        (successful.unwrap_or(0) as f64 / total_optimizations as f64 * 100.0).round() as i64
+    } else {
+        0
+    };
+
+    Ok(OptimizationRecordStats {
+        total_records,
+        total_optimizations,
+        model_usage,
+        average_response_time: totals.get::<Option<f64>, _>("avg_time").unwrap_or(0.0).round() as i64,
+        success_rate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, model: &str, prompt: &str) -> ModelResult {
+        ModelResult {
+            id: id.to_string(),
+            record_id: String::new(),
+            model_name: model.to_string(),
+            optimized_prompt: prompt.to_string(),
+            tokens_used: 10,
+            response_time: 100,
+            status: "success".to_string(),
+            user_rating: None,
+            error: None,
+            selected: false,
+        }
+    }
+
+    fn record(id: &str, prompt: &str, results: Vec<ModelResult>) -> OptimizationRecord {
+        OptimizationRecord {
+            id: id.to_string(),
+            original_prompt: prompt.to_string(),
+            created_at: Utc::now(),
+            user_language: "zh".to_string(),
+            selected_models: results.iter().map(|r| r.model_name.clone()).collect(),
+            results,
+            task_id: None,
+            favorite: false,
+        }
+    }
+
+    async fn pool() -> SqlitePool {
+        let db = crate::services::DatabaseService::new("sqlite::memory:").await.unwrap();
+        init_optimization_record_tables(db.get_pool()).await.unwrap();
+        db.get_pool().clone()
+    }
+
+    async fn history_prompts(pool: &SqlitePool) -> Vec<String> {
+        sqlx::query("SELECT optimized_prompt FROM optimization_history ORDER BY optimized_prompt")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|row| row.get("optimized_prompt"))
+            .collect()
+    }
+
+    async fn result_ids(pool: &SqlitePool, request_id: &str) -> Vec<String> {
+        sqlx::query("SELECT id FROM optimization_results WHERE request_id = ? ORDER BY position")
+            .bind(request_id)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|row| row.get("id"))
+            .collect()
+    }
+
+    #[test]
+    fn content_hash_ignores_ids_time_and_result_order() {
+        let a = record("a", " 写一个排序函数 ", vec![result("1", "glm-4", "优化A"), result("2", "gpt-4", "优化B")]);
+        let mut b = record("b", "写一个排序函数", vec![result("3", "gpt-4", "优化B "), result("4", "glm-4", "优化A")]);
+        b.created_at = a.created_at - chrono::Duration::days(3);
+        assert_eq!(content_hash(&a), content_hash(&b));
+        assert_eq!(content_hash(&a).len(), 16);
+
+        let c = record("c", "写一个排序函数", vec![result("5", "glm-4", "优化C")]);
+        assert_ne!(content_hash(&a), content_hash(&c));
+    }
+
+    #[test]
+    fn content_hash_separates_fields() {
+        // 字段之间有分隔，拼接相同的不同内容不会得到相同哈希
+        let a = record("", "ab", vec![result("", "c", "d")]);
+        let b = record("", "a", vec![result("", "bc", "d")]);
+        assert_ne!(content_hash(&a), content_hash(&b));
+    }
+
+    #[tokio::test]
+    async fn save_record_replaces_results() {
+        let pool = pool().await;
+        let id = save_record(&pool, record("r1", "提示词", vec![result("x", "glm-4", "A"), result("y", "gpt-4", "B")]))
+            .await
+            .unwrap();
+        assert_eq!(id, "r1");
+        assert_eq!(result_ids(&pool, "r1").await, vec!["x", "y"]);
+
+        save_record(&pool, record("r1", "新提示词", vec![result("y", "gpt-4", "C")])).await.unwrap();
+        assert_eq!(result_ids(&pool, "r1").await, vec!["y"]);
+
+        let records = query_records(&pool, &OptimizationRecordFilter::default()).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].original_prompt, "新提示词");
+        assert_eq!(records[0].results[0].optimized_prompt, "C");
+    }
+
+    #[tokio::test]
+    async fn save_record_generates_missing_ids() {
+        let pool = pool().await;
+        let id = save_record(&pool, record(" ", "提示词", vec![result("", "glm-4", "A")])).await.unwrap();
+        assert!(!id.trim().is_empty());
+        assert_eq!(result_ids(&pool, &id).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn save_record_rejects_result_of_other_record() {
+        let pool = pool().await;
+        save_record(&pool, record("r1", "提示词", vec![result("x", "glm-4", "A")])).await.unwrap();
+
+        let error = save_record(&pool, record("r2", "其他", vec![result("x", "glm-4", "B")])).await;
+        assert!(error.is_err());
+        assert_eq!(result_ids(&pool, "r1").await, vec!["x"]);
+        assert!(result_ids(&pool, "r2").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn successful_results_are_mirrored_into_history() {
+        let pool = pool().await;
+        let mut failed = result("z", "gpt-4", "");
+        failed.status = "error".to_string();
+        save_record(&pool, record("r1", "提示词", vec![result("x", "glm-4", "A"), failed])).await.unwrap();
+        assert_eq!(history_prompts(&pool).await, vec!["A"]);
+
+        // 替换记录时旧的历史一并删除
+        save_record(&pool, record("r1", "提示词", vec![result("x", "glm-4", "B")])).await.unwrap();
+        assert_eq!(history_prompts(&pool).await, vec!["B"]);
+
+        let mut tx = pool.begin().await.unwrap();
+        delete_results(&mut tx, &["r1".to_string()]).await.unwrap();
+        tx.commit().await.unwrap();
+        assert!(history_prompts(&pool).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn favorite_is_stored() {
+        let pool = pool().await;
+        let mut favorite = record("r1", "提示词", vec![result("x", "glm-4", "A")]);
+        favorite.favorite = true;
+        save_record(&pool, favorite).await.unwrap();
+
+        let records = query_records(&pool, &OptimizationRecordFilter::default()).await.unwrap();
+        assert!(records[0].favorite);
+    }
+}
@@ -0,0 +1,132 @@
+// 开机自启动 - Linux写入XDG autostart条目，macOS写入LaunchAgent，Windows写入注册表Run项
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+const APP_NAME: &str = "CodingPal";
+
+// 当前可执行文件路径，AppImage运行时使用镜像本身的路径
+fn launch_path() -> Result<PathBuf> {
+    #[cfg(target_os = "linux")]
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Ok(PathBuf::from(appimage));
+    }
+    Ok(std::env::current_exe()?)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn entry_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().config_dir()?.join("autostart").join("codingpal.desktop"))
+}
+
+#[cfg(target_os = "macos")]
+fn entry_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app
+        .path()
+        .home_dir()?
+        .join("Library/LaunchAgents")
+        .join(format!("{}.plist", app.config().identifier)))
+}
+
+// 按桌面条目规范转义Exec中的参数
+#[cfg(all(unix, not(target_os = "macos")))]
+fn entry_contents(_app: &AppHandle, exe: &str) -> String {
+    let mut quoted = String::with_capacity(exe.len() + 2);
+    for c in exe.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec=\"{}\"\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+        APP_NAME, quoted
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn entry_contents(app: &AppHandle, exe: &str) -> String {
+    let escape = |s: &str| s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        escape(&app.config().identifier),
+        escape(exe)
+    )
+}
+
+#[cfg(unix)]
+pub fn is_auto_start_enabled(app: &AppHandle) -> Result<bool> {
+    Ok(entry_path(app)?.exists())
+}
+
+// 注册或移除开机自启动，已注册时会更新为当前程序路径
+#[cfg(unix)]
+pub fn set_auto_start(app: &AppHandle, enabled: bool) -> Result<()> {
+    let path = entry_path(app)?;
+    if !enabled {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+
+    let exe = launch_path()?;
+    let exe = exe.to_str().ok_or_else(|| anyhow!("程序路径包含无效字符"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, entry_contents(app, exe))?;
+    Ok(())
+}
+
+#[cfg(windows)]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+#[cfg(windows)]
+fn reg(args: &[&str]) -> Result<std::process::Output> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    Ok(std::process::Command::new("reg")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()?)
+}
+
+#[cfg(windows)]
+pub fn is_auto_start_enabled(_app: &AppHandle) -> Result<bool> {
+    Ok(reg(&["query", RUN_KEY, "/v", APP_NAME])?.status.success())
+}
+
+#[cfg(windows)]
+pub fn set_auto_start(app: &AppHandle, enabled: bool) -> Result<()> {
+    if !enabled {
+        if is_auto_start_enabled(app)? {
+            let output = reg(&["delete", RUN_KEY, "/v", APP_NAME, "/f"])?;
+            if !output.status.success() {
+                return Err(anyhow!("移除开机启动项失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+            }
+        }
+        return Ok(());
+    }
+
+    let exe = format!("\"{}\"", launch_path()?.display());
+    let output = reg(&["add", RUN_KEY, "/v", APP_NAME, "/t", "REG_SZ", "/d", &exe, "/f"])?;
+    if !output.status.success() {
+        return Err(anyhow!("写入开机启动项失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
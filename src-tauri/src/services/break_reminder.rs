@@ -0,0 +1,370 @@
+// 休息提醒 - 按IDE中的实际编码时长（而非墙上时间）提醒休息
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+use super::database::DatabaseService;
+
+pub const BREAK_EVENT: &str = "break://reminder";
+
+pub const BREAK_OUTCOME_TAKEN: &str = "taken";
+pub const BREAK_OUTCOME_SKIPPED: &str = "skipped";
+
+// 采样IDE活动的间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(30);
+// 单次采样最多计入的时长，避免休眠唤醒后一次性累加
+const MAX_SAMPLE_STEP: Duration = Duration::from_secs(90);
+
+pub static BREAK_SCHEDULER: Lazy<Mutex<BreakScheduler>> = Lazy::new(|| {
+    Mutex::new(BreakScheduler::new(BreakConfig::default()))
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakConfig {
+    pub enabled: bool,
+    pub work: Duration,      // 连续编码多久后提醒
+    pub break_len: Duration, // 离开多久算作一次休息
+    pub skip_after: Duration, // 提醒后继续编码多久视为跳过
+}
+
+impl Default for BreakConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            work: Duration::from_secs(50 * 60),
+            break_len: Duration::from_secs(5 * 60),
+            skip_after: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl BreakConfig {
+    pub async fn load(db: &DatabaseService) -> anyhow::Result<Self> {
+        let defaults = Self::default();
+        let minutes = |value: Option<String>, default: Duration| {
+            value
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|v| *v > 0)
+                .map(|v| Duration::from_secs(v * 60))
+                .unwrap_or(default)
+        };
+
+        Ok(Self {
+            enabled: db.get_setting("break_reminders_enabled").await?.map_or(defaults.enabled, |v| v == "true"),
+            work: minutes(db.get_setting("break_work_minutes").await?, defaults.work),
+            break_len: minutes(db.get_setting("break_minutes").await?, defaults.break_len),
+            skip_after: minutes(db.get_setting("break_skip_minutes").await?, defaults.skip_after),
+        })
+    }
+}
+
+// 调度器产生的事件
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BreakEvent {
+    Due { worked_seconds: u64 },
+    Taken { worked_seconds: u64, break_seconds: u64 },
+    Skipped { worked_seconds: u64 },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakPhase {
+    Working,
+    Due,  // 已提醒，等待休息
+    Away, // 用户离开，计时暂停
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BreakStatus {
+    pub enabled: bool,
+    pub phase: BreakPhase,
+    pub worked_seconds: u64,
+    pub work_seconds: u64,
+}
+
+// 只根据活动采样推进的调度器，时间由调用方传入，不依赖界面
+#[derive(Debug, Clone)]
+pub struct BreakScheduler {
+    config: BreakConfig,
+    worked: Duration,
+    worked_since_due: Duration,
+    due: bool,
+    last_active: Option<Instant>, // 上一次活跃采样，离开后清空
+    idle_since: Option<Instant>,
+    rested: bool, // 本次离开已计为休息
+}
+
+impl BreakScheduler {
+    pub fn new(config: BreakConfig) -> Self {
+        Self {
+            config,
+            worked: Duration::ZERO,
+            worked_since_due: Duration::ZERO,
+            due: false,
+            last_active: None,
+            idle_since: None,
+            rested: false,
+        }
+    }
+
+    pub fn set_config(&mut self, config: BreakConfig) {
+        if !config.enabled {
+            self.reset();
+        }
+        self.config = config;
+    }
+
+    pub fn status(&self) -> BreakStatus {
+        let phase = if self.idle_since.is_some() {
+            BreakPhase::Away
+        } else if self.due {
+            BreakPhase::Due
+        } else {
+            BreakPhase::Working
+        };
+        BreakStatus {
+            enabled: self.config.enabled,
+            phase,
+            worked_seconds: self.worked.as_secs(),
+            work_seconds: self.config.work.as_secs(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.worked = Duration::ZERO;
+        self.worked_since_due = Duration::ZERO;
+        self.due = false;
+    }
+
+    // 输入一次活动采样，编码时累计时长，离开时暂停计时，离开足够久视为休息
+    pub fn observe(&mut self, active: bool, now: Instant) -> Option<BreakEvent> {
+        // 只计入两次活跃采样之间的时长，离开后的第一次活跃采样不计时
+        let step = if active {
+            self.last_active
+                .map(|last| now.duration_since(last).min(MAX_SAMPLE_STEP))
+                .unwrap_or_default()
+        } else {
+            Duration::ZERO
+        };
+        self.last_active = active.then_some(now);
+
+        if !self.config.enabled {
+            return None;
+        }
+
+        if !active {
+            let idle_since = *self.idle_since.get_or_insert(now);
+            let idle = now.duration_since(idle_since);
+            if idle < self.config.break_len || self.rested {
+                return None;
+            }
+
+            // 未提醒时自行离开也重新开始计时，但只记录提醒后的休息
+            self.rested = true;
+            let event = self.due.then_some(BreakEvent::Taken {
+                worked_seconds: self.worked.as_secs(),
+                break_seconds: idle.as_secs(),
+            });
+            self.reset();
+            return event;
+        }
+
+        self.idle_since = None;
+        self.rested = false;
+        self.worked += step;
+
+        if self.due {
+            self.worked_since_due += step;
+            if self.worked_since_due >= self.config.skip_after {
+                return self.skip();
+            }
+            return None;
+        }
+
+        if self.worked >= self.config.work {
+            self.due = true;
+            self.worked_since_due = Duration::ZERO;
+            return Some(BreakEvent::Due { worked_seconds: self.worked.as_secs() });
+        }
+
+        None
+    }
+
+    // 跳过本次休息，重新开始计时
+    pub fn skip(&mut self) -> Option<BreakEvent> {
+        if !self.due {
+            return None;
+        }
+        let event = BreakEvent::Skipped { worked_seconds: self.worked.as_secs() };
+        self.reset();
+        Some(event)
+    }
+}
+
+// 发送通知、事件并记录休息结果
+pub async fn handle_break_event(app: &AppHandle, db: &DatabaseService, event: &BreakEvent) {
+    let _ = app.emit(BREAK_EVENT, event);
+
+    let logged = match event {
+        BreakEvent::Due { worked_seconds } => {
+            let _ = app.notification()
+                .builder()
+                .title("该休息一下了")
+                .body(format!("已经连续编码{}分钟，起身活动一下吧", worked_seconds / 60))
+                .show();
+            Ok(0)
+        }
+        BreakEvent::Taken { worked_seconds, break_seconds } => {
+            db.save_break_log(BREAK_OUTCOME_TAKEN, *worked_seconds as i64, *break_seconds as i64).await
+        }
+        BreakEvent::Skipped { worked_seconds } => {
+            db.save_break_log(BREAK_OUTCOME_SKIPPED, *worked_seconds as i64, 0).await
+        }
+    };
+    if let Err(e) = logged {
+        eprintln!("保存休息记录失败: {}", e);
+    }
+}
+
+// 启动休息提醒：定期采样IDE活动，每次采样前重新读取设置
+pub fn start_break_reminders(app: AppHandle, db: DatabaseService) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match BreakConfig::load(&db).await {
+                Ok(config) => {
+                    if let Ok(mut scheduler) = BREAK_SCHEDULER.lock() {
+                        scheduler.set_config(config);
+                    }
+                }
+                Err(e) => eprintln!("读取休息提醒设置失败: {}", e),
+            }
+
+            // 暂停监控时没有进程数据，视为离开
+            let active = crate::PROCESS_MONITOR
+                .lock()
+                .map(|mut monitor| monitor.is_ide_active())
+                .unwrap_or(false);
+
+            let event = BREAK_SCHEDULER
+                .lock()
+                .ok()
+                .and_then(|mut scheduler| scheduler.observe(active, Instant::now()));
+
+            if let Some(event) = event {
+                handle_break_event(&app, &db, &event).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn config() -> BreakConfig {
+        BreakConfig {
+            enabled: true,
+            work: 10 * MINUTE,
+            break_len: 2 * MINUTE,
+            skip_after: 3 * MINUTE,
+        }
+    }
+
+    // 从start起每30秒采样一次，返回最后时刻和产生的事件
+    fn sample(
+        scheduler: &mut BreakScheduler,
+        start: Instant,
+        length: Duration,
+        active: bool,
+    ) -> (Instant, Vec<BreakEvent>) {
+        let mut events = Vec::new();
+        let mut now = start;
+        while now <= start + length {
+            events.extend(scheduler.observe(active, now));
+            now += SAMPLE_INTERVAL;
+        }
+        (now - SAMPLE_INTERVAL, events)
+    }
+
+    #[test]
+    fn due_after_work_duration() {
+        let mut scheduler = BreakScheduler::new(config());
+        let (_, events) = sample(&mut scheduler, Instant::now(), 10 * MINUTE, true);
+
+        assert_eq!(events, vec![BreakEvent::Due { worked_seconds: 600 }]);
+        assert_eq!(scheduler.status().phase, BreakPhase::Due);
+    }
+
+    #[test]
+    fn idle_gap_is_not_counted_as_work() {
+        let mut scheduler = BreakScheduler::new(config());
+        let (now, _) = sample(&mut scheduler, Instant::now(), 5 * MINUTE, true);
+        let (now, _) = sample(&mut scheduler, now + SAMPLE_INTERVAL, MINUTE, false);
+
+        scheduler.observe(true, now + SAMPLE_INTERVAL);
+        assert_eq!(scheduler.status().worked_seconds, 300);
+    }
+
+    #[test]
+    fn sample_step_is_capped() {
+        let mut scheduler = BreakScheduler::new(config());
+        let start = Instant::now();
+        scheduler.observe(true, start);
+        scheduler.observe(true, start + 30 * MINUTE);
+
+        assert_eq!(scheduler.status().worked_seconds, MAX_SAMPLE_STEP.as_secs());
+    }
+
+    #[test]
+    fn break_after_reminder_is_taken() {
+        let mut scheduler = BreakScheduler::new(config());
+        let (now, _) = sample(&mut scheduler, Instant::now(), 10 * MINUTE, true);
+        let (now, events) = sample(&mut scheduler, now + SAMPLE_INTERVAL, 3 * MINUTE, false);
+
+        assert_eq!(events, vec![BreakEvent::Taken { worked_seconds: 600, break_seconds: 120 }]);
+        assert_eq!(scheduler.status().phase, BreakPhase::Away);
+
+        scheduler.observe(true, now + SAMPLE_INTERVAL);
+        assert_eq!(scheduler.status().phase, BreakPhase::Working);
+        assert_eq!(scheduler.status().worked_seconds, 0);
+    }
+
+    #[test]
+    fn short_absence_keeps_reminder() {
+        let mut scheduler = BreakScheduler::new(config());
+        let (now, _) = sample(&mut scheduler, Instant::now(), 10 * MINUTE, true);
+        let (now, events) = sample(&mut scheduler, now + SAMPLE_INTERVAL, MINUTE, false);
+        assert!(events.is_empty());
+
+        scheduler.observe(true, now + SAMPLE_INTERVAL);
+        assert_eq!(scheduler.status().phase, BreakPhase::Due);
+    }
+
+    #[test]
+    fn working_through_reminder_is_skipped() {
+        let mut scheduler = BreakScheduler::new(config());
+        let (now, _) = sample(&mut scheduler, Instant::now(), 10 * MINUTE, true);
+        let (_, events) = sample(&mut scheduler, now + SAMPLE_INTERVAL, 3 * MINUTE, true);
+
+        assert_eq!(events, vec![BreakEvent::Skipped { worked_seconds: 780 }]);
+        assert_eq!(scheduler.status().phase, BreakPhase::Working);
+    }
+
+    #[test]
+    fn disabled_scheduler_stays_silent() {
+        let mut scheduler = BreakScheduler::new(BreakConfig { enabled: false, ..config() });
+        let (_, events) = sample(&mut scheduler, Instant::now(), 60 * MINUTE, true);
+
+        assert!(events.is_empty());
+        assert_eq!(scheduler.status().worked_seconds, 0);
+    }
+}
@@ -0,0 +1,266 @@
+// 桌面伙伴状态机 - 根据后端信号决定头像显示welcome/thinking/working/rest
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+pub const COMPANION_STATE_EVENT: &str = "companion://state";
+
+// 状态机检查的间隔
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+// 采样IDE进程的间隔
+const IDE_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+pub static COMPANION: Lazy<Mutex<CompanionMachine>> = Lazy::new(|| {
+    Mutex::new(CompanionMachine::new(Instant::now()))
+});
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompanionState {
+    Welcome,
+    Thinking,
+    Working,
+    Rest,
+}
+
+impl CompanionState {
+    // 优先级高的状态可以立即打断当前状态
+    pub fn priority(&self) -> u8 {
+        match self {
+            CompanionState::Thinking => 3,
+            CompanionState::Working => 2,
+            CompanionState::Welcome => 1,
+            CompanionState::Rest => 0,
+        }
+    }
+
+    // 切换到优先级更低的状态前，当前状态至少显示的时长
+    pub fn min_display(&self) -> Duration {
+        match self {
+            CompanionState::Welcome => Duration::from_secs(3),
+            CompanionState::Thinking => Duration::from_secs(1),
+            CompanionState::Working => Duration::from_secs(5),
+            CompanionState::Rest => Duration::ZERO,
+        }
+    }
+}
+
+// 状态切换事件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompanionStateChange {
+    pub state: CompanionState,
+    pub previous: Option<CompanionState>,
+}
+
+// 不依赖界面和系统时间的状态机，信号由调用方写入，时间由调用方传入
+#[derive(Debug, Clone)]
+pub struct CompanionMachine {
+    state: CompanionState,
+    since: Instant,
+    started_at: Instant,
+    optimizations: u32,
+    ide_busy: bool,
+}
+
+impl CompanionMachine {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            state: CompanionState::Welcome,
+            since: now,
+            started_at: now,
+            optimizations: 0,
+            ide_busy: false,
+        }
+    }
+
+    pub fn state(&self) -> CompanionState {
+        self.state
+    }
+
+    pub fn begin_optimization(&mut self) {
+        self.optimizations += 1;
+    }
+
+    pub fn end_optimization(&mut self) {
+        self.optimizations = self.optimizations.saturating_sub(1);
+    }
+
+    pub fn set_ide_busy(&mut self, busy: bool) {
+        self.ide_busy = busy;
+    }
+
+    // 当前信号对应的状态
+    pub fn desired(&self, now: Instant) -> CompanionState {
+        if self.optimizations > 0 {
+            CompanionState::Thinking
+        } else if self.ide_busy {
+            CompanionState::Working
+        } else if now.duration_since(self.started_at) < CompanionState::Welcome.min_display() {
+            CompanionState::Welcome
+        } else {
+            CompanionState::Rest
+        }
+    }
+
+    // 推进状态机，状态发生变化时返回切换事件
+    pub fn update(&mut self, now: Instant) -> Option<CompanionStateChange> {
+        let desired = self.desired(now);
+        if desired == self.state {
+            return None;
+        }
+
+        let preempts = desired.priority() > self.state.priority();
+        let held = now.duration_since(self.since) < self.state.min_display();
+        if !preempts && held {
+            return None;
+        }
+
+        let previous = std::mem::replace(&mut self.state, desired);
+        self.since = now;
+        Some(CompanionStateChange {
+            state: desired,
+            previous: Some(previous),
+        })
+    }
+}
+
+// 优化请求进行期间保持thinking，离开作用域（包括出错返回）时自动结束
+pub struct OptimizationActivity;
+
+impl OptimizationActivity {
+    pub fn begin() -> Self {
+        if let Ok(mut companion) = COMPANION.lock() {
+            companion.begin_optimization();
+        }
+        OptimizationActivity
+    }
+}
+
+impl Drop for OptimizationActivity {
+    fn drop(&mut self) {
+        if let Ok(mut companion) = COMPANION.lock() {
+            companion.end_optimization();
+        }
+    }
+}
+
+// 启动状态机循环：定期采样IDE进程并在状态变化时发送事件
+pub fn start_companion(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        let mut last_sample: Option<Instant> = None;
+
+        if let Ok(companion) = COMPANION.lock() {
+            let _ = app.emit(COMPANION_STATE_EVENT, &CompanionStateChange {
+                state: companion.state(),
+                previous: None,
+            });
+        }
+
+        loop {
+            interval.tick().await;
+            let now = Instant::now();
+
+            let sample_due = last_sample.map_or(true, |at| now.duration_since(at) >= IDE_SAMPLE_INTERVAL);
+            let ide_busy = if sample_due {
+                last_sample = Some(now);
+                crate::PROCESS_MONITOR.lock().ok().map(|mut monitor| monitor.is_ide_active())
+            } else {
+                None
+            };
+
+            let change = match COMPANION.lock() {
+                Ok(mut companion) => {
+                    if let Some(busy) = ide_busy {
+                        companion.set_ide_busy(busy);
+                    }
+                    companion.update(now)
+                }
+                Err(_) => None,
+            };
+
+            if let Some(change) = change {
+                let _ = app.emit(COMPANION_STATE_EVENT, &change);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(base: Instant, ms: u64) -> Instant {
+        base + Duration::from_millis(ms)
+    }
+
+    fn changed_to(change: Option<CompanionStateChange>) -> Option<CompanionState> {
+        change.map(|change| change.state)
+    }
+
+    #[test]
+    fn welcome_then_rest() {
+        let base = Instant::now();
+        let mut machine = CompanionMachine::new(base);
+
+        assert_eq!(changed_to(machine.update(at(base, 2900))), None);
+        let change = machine.update(at(base, 3000)).unwrap();
+        assert_eq!(change.state, CompanionState::Rest);
+        assert_eq!(change.previous, Some(CompanionState::Welcome));
+    }
+
+    #[test]
+    fn higher_priority_preempts_immediately() {
+        let base = Instant::now();
+        let mut machine = CompanionMachine::new(base);
+
+        machine.set_ide_busy(true);
+        assert_eq!(changed_to(machine.update(at(base, 100))), Some(CompanionState::Working));
+
+        machine.begin_optimization();
+        assert_eq!(changed_to(machine.update(at(base, 200))), Some(CompanionState::Thinking));
+    }
+
+    #[test]
+    fn lower_priority_waits_for_min_display() {
+        let base = Instant::now();
+        let mut machine = CompanionMachine::new(base);
+        machine.set_ide_busy(true);
+        machine.update(at(base, 0));
+
+        // working至少显示5秒才能回到rest
+        machine.set_ide_busy(false);
+        assert_eq!(changed_to(machine.update(at(base, 4999))), None);
+        assert_eq!(changed_to(machine.update(at(base, 5000))), Some(CompanionState::Rest));
+    }
+
+    #[test]
+    fn thinking_falls_back_to_working_after_hold() {
+        let base = Instant::now();
+        let mut machine = CompanionMachine::new(base);
+        machine.set_ide_busy(true);
+        machine.begin_optimization();
+        assert_eq!(changed_to(machine.update(at(base, 0))), Some(CompanionState::Thinking));
+
+        machine.end_optimization();
+        assert_eq!(changed_to(machine.update(at(base, 500))), None);
+        assert_eq!(changed_to(machine.update(at(base, 1000))), Some(CompanionState::Working));
+    }
+
+    #[test]
+    fn overlapping_optimizations_keep_thinking() {
+        let base = Instant::now();
+        let mut machine = CompanionMachine::new(base);
+        machine.begin_optimization();
+        machine.begin_optimization();
+        machine.update(at(base, 0));
+
+        machine.end_optimization();
+        assert_eq!(machine.desired(at(base, 5000)), CompanionState::Thinking);
+        machine.end_optimization();
+        machine.end_optimization();
+        assert_eq!(machine.desired(at(base, 5000)), CompanionState::Rest);
+    }
+}
@@ -1,4 +1,6 @@
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
 use sqlx::{SqlitePool, Row};
+use std::path::Path;
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -31,9 +33,46 @@ pub struct OptimizationHistory {
     pub confidence: f32,
     pub tokens_used: i32,
     pub processing_time_ms: i32,
+    pub task_id: Option<i64>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub rating: Option<i32>, // 用户评分，1-5
+    #[serde(default)]
+    pub improvements: Vec<String>,
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskFolder {
+    pub id: i64,
+    pub folder_name: String,
+    pub folder_path: String,
+    pub status: String,
+    pub repo_path: Option<String>,
+    pub branch: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskStatusTransition {
+    pub id: i64,
+    pub task_id: i64,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakLog {
+    pub id: i64,
+    pub outcome: String,
+    pub worked_seconds: i64,
+    pub break_seconds: i64,
+    pub logged_at: DateTime<Utc>,
+}
+
 #[derive(Clone)]
 pub struct DatabaseService {
     pool: SqlitePool,
@@ -41,7 +80,16 @@ pub struct DatabaseService {
 
 impl DatabaseService {
     pub async fn new(database_url: &str) -> Result<Self> {
-        let pool = SqlitePool::connect(database_url).await?;
+        Self::init(SqlitePool::connect(database_url).await?).await
+    }
+    
+    // 打开数据库文件，不存在时创建
+    pub async fn open(path: &Path) -> Result<Self> {
+        let options = SqliteConnectOptions::new().filename(path).create_if_missing(true);
+        Self::init(SqlitePool::connect_with(options).await?).await
+    }
+    
+    async fn init(pool: SqlitePool) -> Result<Self> {
         let service = Self { pool };
         service.init_tables().await?;
         service.init_default_settings().await?;
@@ -87,25 +135,101 @@ impl DatabaseService {
                 confidence REAL DEFAULT 0.0,
                 tokens_used INTEGER DEFAULT 0,
                 processing_time_ms INTEGER DEFAULT 0,
+                task_id INTEGER,
+                model VARCHAR(50),
+                rating INTEGER,
+                improvements TEXT NOT NULL DEFAULT '[]',
                 created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
             )
             "#
         ).execute(&self.pool).await?;
         
+        // 优化历史的全文索引，trigram分词以支持中文子串搜索
+        sqlx::query(
+            r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS optimization_history_fts USING fts5(
+                original_prompt,
+                optimized_prompt,
+                content = 'optimization_history',
+                content_rowid = 'id',
+                tokenize = 'trigram'
+            )
+            "#
+        ).execute(&self.pool).await?;
+        
+        // 通过触发器保持全文索引与历史表同步
+        sqlx::query(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS optimization_history_ai AFTER INSERT ON optimization_history BEGIN
+                INSERT INTO optimization_history_fts (rowid, original_prompt, optimized_prompt)
+                VALUES (new.id, new.original_prompt, new.optimized_prompt);
+            END
+            "#
+        ).execute(&self.pool).await?;
+        
+        sqlx::query(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS optimization_history_ad AFTER DELETE ON optimization_history BEGIN
+                INSERT INTO optimization_history_fts (optimization_history_fts, rowid, original_prompt, optimized_prompt)
+                VALUES ('delete', old.id, old.original_prompt, old.optimized_prompt);
+            END
+            "#
+        ).execute(&self.pool).await?;
+        
+        sqlx::query(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS optimization_history_au AFTER UPDATE OF original_prompt, optimized_prompt ON optimization_history BEGIN
+                INSERT INTO optimization_history_fts (optimization_history_fts, rowid, original_prompt, optimized_prompt)
+                VALUES ('delete', old.id, old.original_prompt, old.optimized_prompt);
+                INSERT INTO optimization_history_fts (rowid, original_prompt, optimized_prompt)
+                VALUES (new.id, new.original_prompt, new.optimized_prompt);
+            END
+            "#
+        ).execute(&self.pool).await?;
+        
         // 创建任务文件夹表
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS task_folders (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 folder_name VARCHAR(100) NOT NULL,
-                folder_path VARCHAR(500) NOT NULL,
+                folder_path VARCHAR(500) UNIQUE NOT NULL,
                 status VARCHAR(20) DEFAULT 'active',
+                repo_path VARCHAR(500),
+                branch VARCHAR(200),
                 created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
                 updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
             )
             "#
         ).execute(&self.pool).await?;
         
+        // 创建任务状态变更表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS task_status_transitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id INTEGER NOT NULL,
+                from_status VARCHAR(20),
+                to_status VARCHAR(20) NOT NULL,
+                changed_at TIMESTAMP NOT NULL,
+                FOREIGN KEY (task_id) REFERENCES task_folders(id)
+            )
+            "#
+        ).execute(&self.pool).await?;
+        
+        // 创建休息记录表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS break_logs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                outcome VARCHAR(20) NOT NULL,
+                worked_seconds INTEGER NOT NULL,
+                break_seconds INTEGER DEFAULT 0,
+                logged_at TIMESTAMP NOT NULL
+            )
+            "#
+        ).execute(&self.pool).await?;
+        
         Ok(())
     }
     
@@ -115,6 +239,18 @@ impl DatabaseService {
             ("auto_start", "false"),
             ("window_always_on_top", "true"),
             ("monitoring_interval", "5000"),
+            ("task_root", "CodingPal/tasks"),
+            ("archive_dir", "CodingPal/archives"),
+            ("dataset_dir", "CodingPal/datasets"),
+            ("active_task_id", ""),
+            ("gesture_recognition_enabled", "true"),
+            ("break_reminders_enabled", "true"),
+            ("break_work_minutes", "50"),
+            ("break_minutes", "5"),
+            ("break_skip_minutes", "10"),
+            ("gesture_retention_days", "90"),
+            ("gesture_retention_max_records", "10000"),
+            ("gesture_retention_keypoint_records", "2000"),
         ];
         
         for (key, value) in default_settings {
@@ -172,50 +308,337 @@ impl DatabaseService {
     }
     
     pub async fn save_optimization_history(&self, history: &OptimizationHistory) -> Result<i64> {
+        let mut conn = self.pool.acquire().await?;
+        Ok(insert_optimization_history(&mut conn, history).await?)
+    }
+    
+    pub async fn get_optimization_history(&self, limit: i32) -> Result<Vec<OptimizationHistory>> {
+        let rows = sqlx::query(
+            "SELECT * FROM optimization_history ORDER BY created_at DESC LIMIT ?"
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.iter().map(optimization_history_from_row).collect())
+    }
+    
+    // 设置或清除优化记录的评分
+    pub async fn set_optimization_rating(&self, id: i64, rating: Option<i32>) -> Result<bool> {
+        let result = sqlx::query("UPDATE optimization_history SET rating = ? WHERE id = ?")
+            .bind(rating)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    pub async fn get_optimization_record(&self, id: i64) -> Result<Option<OptimizationHistory>> {
+        let row = sqlx::query("SELECT * FROM optimization_history WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(row.as_ref().map(optimization_history_from_row))
+    }
+    
+    pub async fn get_task_optimization_history(&self, task_id: i64) -> Result<Vec<OptimizationHistory>> {
+        let rows = sqlx::query(
+            "SELECT * FROM optimization_history WHERE task_id = ? ORDER BY created_at"
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.iter().map(optimization_history_from_row).collect())
+    }
+    
+    // 登记任务文件夹，路径已存在时返回原有记录ID
+    pub async fn ensure_task_folder(&self, folder_name: &str, folder_path: &str) -> Result<i64> {
+        let inserted = sqlx::query(
+            "INSERT INTO task_folders (folder_name, folder_path) VALUES (?, ?) ON CONFLICT(folder_path) DO NOTHING"
+        )
+        .bind(folder_name)
+        .bind(folder_path)
+        .execute(&self.pool)
+        .await?
+        .rows_affected() > 0;
+        
+        let row = sqlx::query("SELECT id, status FROM task_folders WHERE folder_path = ?")
+            .bind(folder_path)
+            .fetch_one(&self.pool)
+            .await?;
+        let id: i64 = row.get("id");
+        
+        if inserted {
+            let status: String = row.get("status");
+            self.save_task_status_transition(id, None, &status, Utc::now()).await?;
+        }
+        
+        Ok(id)
+    }
+    
+    // 导入任务时按原状态登记，不经过默认的active状态
+    pub async fn import_task_folder(
+        &self,
+        folder_name: &str,
+        folder_path: &str,
+        status: &str,
+        created_at: DateTime<Utc>,
+    ) -> Result<i64> {
         let result = sqlx::query(
-            r#"
-            INSERT INTO optimization_history 
-            (original_prompt, optimized_prompt, confidence, tokens_used, processing_time_ms)
-            VALUES (?, ?, ?, ?, ?)
-            "#
+            "INSERT INTO task_folders (folder_name, folder_path, status, created_at) VALUES (?, ?, ?, ?)"
         )
-        .bind(&history.original_prompt)
-        .bind(&history.optimized_prompt)
-        .bind(history.confidence)
-        .bind(history.tokens_used)
-        .bind(history.processing_time_ms)
+        .bind(folder_name)
+        .bind(folder_path)
+        .bind(status)
+        .bind(created_at)
         .execute(&self.pool)
         .await?;
         
         Ok(result.last_insert_rowid())
     }
     
-    pub async fn get_optimization_history(&self, limit: i32) -> Result<Vec<OptimizationHistory>> {
+    pub async fn delete_task_folder(&self, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM task_status_transitions WHERE task_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        sqlx::query("DELETE FROM task_folders WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn get_task_folders(&self) -> Result<Vec<TaskFolder>> {
+        let rows = sqlx::query("SELECT * FROM task_folders ORDER BY created_at DESC")
+            .fetch_all(&self.pool)
+            .await?;
+        
+        Ok(rows.iter().map(task_folder_from_row).collect())
+    }
+    
+    pub async fn get_task_folder(&self, id: i64) -> Result<Option<TaskFolder>> {
+        let row = sqlx::query("SELECT * FROM task_folders WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(row.as_ref().map(task_folder_from_row))
+    }
+    
+    pub async fn get_task_folder_by_path(&self, folder_path: &str) -> Result<Option<TaskFolder>> {
+        let row = sqlx::query("SELECT * FROM task_folders WHERE folder_path = ?")
+            .bind(folder_path)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(row.as_ref().map(task_folder_from_row))
+    }
+    
+    pub async fn rename_task_folder(&self, id: i64, folder_name: &str, folder_path: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE task_folders SET folder_name = ?, folder_path = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
+        )
+        .bind(folder_name)
+        .bind(folder_path)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    // 更新任务状态，并记录状态变更
+    pub async fn update_task_status(&self, id: i64, status: &str) -> Result<()> {
+        let previous: Option<String> = sqlx::query("SELECT status FROM task_folders WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|row| row.get("status"));
+        
+        sqlx::query(
+            "UPDATE task_folders SET status = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
+        )
+        .bind(status)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        
+        if let Some(previous) = previous.filter(|previous| previous != status) {
+            self.save_task_status_transition(id, Some(&previous), status, Utc::now()).await?;
+        }
+        
+        Ok(())
+    }
+    
+    pub async fn save_task_status_transition(
+        &self,
+        task_id: i64,
+        from_status: Option<&str>,
+        to_status: &str,
+        changed_at: DateTime<Utc>,
+    ) -> Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO task_status_transitions (task_id, from_status, to_status, changed_at) VALUES (?, ?, ?, ?)"
+        )
+        .bind(task_id)
+        .bind(from_status)
+        .bind(to_status)
+        .bind(changed_at)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.last_insert_rowid())
+    }
+    
+    pub async fn get_task_status_transitions(&self, task_id: i64) -> Result<Vec<TaskStatusTransition>> {
         let rows = sqlx::query(
-            "SELECT * FROM optimization_history ORDER BY created_at DESC LIMIT ?"
+            "SELECT * FROM task_status_transitions WHERE task_id = ? ORDER BY changed_at, id"
         )
-        .bind(limit)
+        .bind(task_id)
         .fetch_all(&self.pool)
         .await?;
         
-        let mut histories = Vec::new();
+        let mut transitions = Vec::new();
         for row in rows {
-            histories.push(OptimizationHistory {
+            transitions.push(TaskStatusTransition {
                 id: row.get("id"),
-                original_prompt: row.get("original_prompt"),
-                optimized_prompt: row.get("optimized_prompt"),
-                confidence: row.get("confidence"),
-                tokens_used: row.get("tokens_used"),
-                processing_time_ms: row.get("processing_time_ms"),
-                created_at: row.get("created_at"),
+                task_id: row.get("task_id"),
+                from_status: row.get("from_status"),
+                to_status: row.get("to_status"),
+                changed_at: row.get("changed_at"),
             });
         }
         
-        Ok(histories)
+        Ok(transitions)
+    }
+    
+    // 当前激活的任务，优化记录会关联到该任务
+    pub async fn get_active_task_id(&self) -> Result<Option<i64>> {
+        Ok(self.get_setting("active_task_id")
+            .await?
+            .and_then(|value| value.parse().ok()))
+    }
+    
+    // 记录任务对应的git工作树
+    pub async fn set_task_worktree(&self, id: i64, repo_path: &str, branch: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE task_folders SET repo_path = ?, branch = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
+        )
+        .bind(repo_path)
+        .bind(branch)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn touch_task_folder(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE task_folders SET updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn save_break_log(&self, outcome: &str, worked_seconds: i64, break_seconds: i64) -> Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO break_logs (outcome, worked_seconds, break_seconds, logged_at) VALUES (?, ?, ?, ?)"
+        )
+        .bind(outcome)
+        .bind(worked_seconds)
+        .bind(break_seconds)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.last_insert_rowid())
+    }
+    
+    pub async fn get_break_logs(&self, limit: i32) -> Result<Vec<BreakLog>> {
+        let rows = sqlx::query(
+            "SELECT * FROM break_logs ORDER BY logged_at DESC, id DESC LIMIT ?"
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows
+            .iter()
+            .map(|row| BreakLog {
+                id: row.get("id"),
+                outcome: row.get("outcome"),
+                worked_seconds: row.get("worked_seconds"),
+                break_seconds: row.get("break_seconds"),
+                logged_at: row.get("logged_at"),
+            })
+            .collect())
     }
     
     // 获取数据库连接池的方法，供其他服务使用
     pub fn get_pool(&self) -> &SqlitePool {
         &self.pool
     }
-}
\ No newline at end of file
+}
+
+// 写入一条优化历史，可在调用方的事务中执行
+pub(crate) async fn insert_optimization_history(conn: &mut SqliteConnection, history: &OptimizationHistory) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO optimization_history 
+        (original_prompt, optimized_prompt, confidence, tokens_used, processing_time_ms, task_id, model, rating, improvements, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#
+    )
+    .bind(&history.original_prompt)
+    .bind(&history.optimized_prompt)
+    .bind(history.confidence)
+    .bind(history.tokens_used)
+    .bind(history.processing_time_ms)
+    .bind(history.task_id)
+    .bind(&history.model)
+    .bind(history.rating)
+    .bind(serde_json::to_string(&history.improvements).unwrap_or_else(|_| "[]".to_string()))
+    .bind(history.created_at)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+pub(crate) fn optimization_history_from_row(row: &sqlx::sqlite::SqliteRow) -> OptimizationHistory {
+    let improvements: String = row.get("improvements");
+    OptimizationHistory {
+        id: row.get("id"),
+        original_prompt: row.get("original_prompt"),
+        optimized_prompt: row.get("optimized_prompt"),
+        confidence: row.get("confidence"),
+        tokens_used: row.get("tokens_used"),
+        processing_time_ms: row.get("processing_time_ms"),
+        task_id: row.get("task_id"),
+        model: row.get("model"),
+        rating: row.get("rating"),
+        improvements: serde_json::from_str(&improvements).unwrap_or_default(),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn task_folder_from_row(row: &sqlx::sqlite::SqliteRow) -> TaskFolder {
+    TaskFolder {
+        id: row.get("id"),
+        folder_name: row.get("folder_name"),
+        folder_path: row.get("folder_path"),
+        status: row.get("status"),
+        repo_path: row.get("repo_path"),
+        branch: row.get("branch"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
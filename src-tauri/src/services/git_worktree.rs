@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::process::Command;
+use anyhow::{Result, anyhow};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorktreeDiffStats {
+    pub files_changed: u32,
+    pub insertions: u32,
+    pub deletions: u32,
+    pub untracked_files: u32,
+    pub commits_ahead: u32,
+}
+
+async fn run_git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| anyhow!("无法执行git: {}", e))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} 失败: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// 默认的任务分支名
+pub fn default_task_branch(folder_name: &str) -> String {
+    format!("codingpal/{}", folder_name)
+}
+
+// 返回仓库的顶层目录，同时校验路径确实是git仓库
+pub async fn repo_toplevel(repo_path: &Path) -> Result<String> {
+    let toplevel = run_git(repo_path, &["rev-parse", "--show-toplevel"]).await?;
+    Ok(toplevel.trim().to_string())
+}
+
+// 在新分支上为仓库创建工作树
+pub async fn create_worktree(repo_path: &Path, worktree_path: &Path, branch: &str) -> Result<()> {
+    run_git(repo_path, &["check-ref-format", "--branch", branch]).await?;
+
+    let worktree_path = worktree_path.to_string_lossy();
+    run_git(repo_path, &["worktree", "add", "-b", branch, worktree_path.as_ref()]).await?;
+
+    Ok(())
+}
+
+pub async fn remove_worktree(repo_path: &Path, worktree_path: &Path, force: bool) -> Result<()> {
+    let worktree_path = worktree_path.to_string_lossy();
+    let mut args = vec!["worktree", "remove"];
+    if force {
+        args.push("--force");
+    }
+    args.push(worktree_path.as_ref());
+
+    run_git(repo_path, &args).await?;
+    run_git(repo_path, &["worktree", "prune"]).await?;
+
+    Ok(())
+}
+
+// 统计工作树中未提交的改动，以及分支上独有的提交数
+pub async fn worktree_diff_stats(worktree_path: &Path, branch: &str) -> Result<WorktreeDiffStats> {
+    let mut stats = WorktreeDiffStats::default();
+
+    let numstat = run_git(worktree_path, &["diff", "--numstat", "HEAD"]).await?;
+    for line in numstat.lines() {
+        let mut parts = line.split('\t');
+        // 二进制文件的增删行数为"-"
+        let insertions = parts.next().and_then(|n| n.parse::<u32>().ok()).unwrap_or(0);
+        let deletions = parts.next().and_then(|n| n.parse::<u32>().ok()).unwrap_or(0);
+        stats.files_changed += 1;
+        stats.insertions += insertions;
+        stats.deletions += deletions;
+    }
+
+    let untracked = run_git(worktree_path, &["ls-files", "--others", "--exclude-standard"]).await?;
+    stats.untracked_files = untracked.lines().filter(|line| !line.is_empty()).count() as u32;
+
+    let exclude = format!("--exclude=refs/heads/{}", branch);
+    let ahead = run_git(
+        worktree_path,
+        &["rev-list", "--count", "HEAD", "--not", exclude.as_str(), "--branches"],
+    )
+    .await?;
+    stats.commits_ahead = ahead.trim().parse().unwrap_or(0);
+
+    Ok(stats)
+}
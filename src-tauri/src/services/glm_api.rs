@@ -74,6 +74,10 @@ impl GLMClient {
         Ok(Self { client, config })
     }
     
+    pub fn model(&self) -> &str {
+        &self.config.model
+    }
+    
     pub async fn test_connection(&self) -> Result<bool> {
         let request = GLMRequest {
             model: self.config.model.clone(),
@@ -1,7 +1,19 @@
 pub mod glm_api;
 pub mod database;
 pub mod process_monitor;
+pub mod task_watcher;
+pub mod git_worktree;
+pub mod task_archive;
+pub mod companion;
+pub mod break_reminder;
+pub mod autostart;
 
 pub use glm_api::*;
 pub use database::*;
-pub use process_monitor::*;
\ No newline at end of file
+pub use process_monitor::*;
+pub use task_watcher::*;
+pub use git_worktree::*;
+pub use task_archive::*;
+pub use companion::*;
+pub use break_reminder::*;
+pub use autostart::*;
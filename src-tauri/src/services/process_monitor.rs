@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 
+// 任一IDE进程CPU占用超过该值视为正在编码，桌面伙伴和休息提醒共用
+pub const IDE_ACTIVE_CPU: f32 = 5.0;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IDEProcess {
     pub pid: u32,
@@ -27,6 +30,7 @@ pub struct ProcessMonitor {
     system: System,
     tracked_processes: HashMap<u32, ProcessInfo>,
     ide_names: Vec<String>,
+    paused: bool,
 }
 
 impl ProcessMonitor {
@@ -48,6 +52,7 @@ impl ProcessMonitor {
             system: System::new_all(),
             tracked_processes: HashMap::new(),
             ide_names,
+            paused: false,
         }
     }
     
@@ -55,7 +60,25 @@ impl ProcessMonitor {
         self.system.refresh_all();
     }
     
+    // 暂停期间不再扫描进程
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+    
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    
+    // 是否有IDE进程正在编码，暂停监控时没有进程数据，视为不活跃
+    pub fn is_ide_active(&mut self) -> bool {
+        self.get_ide_processes().iter().any(|p| p.cpu_usage > IDE_ACTIVE_CPU)
+    }
+
     pub fn get_ide_processes(&mut self) -> Vec<IDEProcess> {
+        if self.paused {
+            return Vec::new();
+        }
+        
         self.refresh();
         let mut processes = Vec::new();
         
@@ -137,6 +160,17 @@ impl ProcessMonitor {
         !self.tracked_processes.is_empty()
     }
     
+    // 已发现的IDE进程名称（去重），不刷新进程列表
+    pub fn get_tracked_process_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.tracked_processes
+            .values()
+            .map(|process| process.name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+    
     pub fn get_tracked_processes(&self) -> &HashMap<u32, ProcessInfo> {
         &self.tracked_processes
     }
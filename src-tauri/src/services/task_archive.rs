@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+use anyhow::{Result, anyhow};
+
+use super::database::{DatabaseService, OptimizationHistory, TaskStatusTransition};
+
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+pub const DEFAULT_ARCHIVE_DIR: &str = "CodingPal/archives";
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const HISTORY_ENTRY: &str = "history.json";
+const FILES_PREFIX: &str = "files/";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledTask {
+    pub folder_name: String,
+    pub status: String,
+    pub branch: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// 任务包清单，记录任务信息与状态变更历史
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskBundleManifest {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub task: BundledTask,
+    pub status_transitions: Vec<TaskStatusTransition>,
+    pub history_count: usize,
+}
+
+pub async fn resolve_archive_dir(db: &DatabaseService) -> Result<PathBuf> {
+    let dir = db.get_setting("archive_dir")
+        .await?
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_ARCHIVE_DIR.to_string());
+
+    std::fs::create_dir_all(&dir)?;
+    Ok(PathBuf::from(dir))
+}
+
+// 将任务文件夹、关联的优化历史和状态变更打包为zip
+pub async fn export_task_bundle(db: &DatabaseService, task_id: i64, archive_dir: &Path) -> Result<PathBuf> {
+    let task = db.get_task_folder(task_id)
+        .await?
+        .ok_or_else(|| anyhow!("任务不存在"))?;
+    let status_transitions = db.get_task_status_transitions(task_id).await?;
+    let history = db.get_task_optimization_history(task_id).await?;
+
+    let manifest = TaskBundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        exported_at: Utc::now(),
+        task: BundledTask {
+            folder_name: task.folder_name.clone(),
+            status: task.status.clone(),
+            branch: task.branch.clone(),
+            created_at: task.created_at,
+            updated_at: task.updated_at,
+        },
+        status_transitions,
+        history_count: history.len(),
+    };
+
+    let bundle_path = archive_dir.join(format!(
+        "{}-{}.zip",
+        task.folder_name,
+        manifest.exported_at.format("%Y%m%d-%H%M%S")
+    ));
+    let folder_path = PathBuf::from(&task.folder_path);
+
+    let target = bundle_path.clone();
+    tokio::task::spawn_blocking(move || write_bundle(&target, &folder_path, &manifest, &history)).await??;
+
+    Ok(bundle_path)
+}
+
+fn write_bundle(
+    bundle_path: &Path,
+    folder_path: &Path,
+    manifest: &TaskBundleManifest,
+    history: &[OptimizationHistory],
+) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(bundle_path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(MANIFEST_ENTRY, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(manifest)?)?;
+
+    zip.start_file(HISTORY_ENTRY, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(history)?)?;
+
+    // 文件夹可能已在应用外被删除，此时只打包清单和历史
+    if folder_path.is_dir() {
+        add_dir_to_zip(&mut zip, folder_path, FILES_PREFIX, options)?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+fn add_dir_to_zip(
+    zip: &mut ZipWriter<File>,
+    dir: &Path,
+    prefix: &str,
+    options: SimpleFileOptions,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        // 工作树的.git只是指向原仓库的链接，不需要打包
+        if name == ".git" {
+            continue;
+        }
+
+        let entry_name = format!("{}{}", prefix, name);
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            zip.add_directory(entry_name.as_str(), options)?;
+            add_dir_to_zip(zip, &entry.path(), &format!("{}/", entry_name), options)?;
+        } else if file_type.is_file() {
+            zip.start_file(entry_name.as_str(), options)?;
+            std::io::copy(&mut File::open(entry.path())?, zip)?;
+        }
+    }
+
+    Ok(())
+}
+
+struct BundleContents {
+    manifest: TaskBundleManifest,
+    history: Vec<OptimizationHistory>,
+}
+
+fn read_bundle(bundle_path: &Path) -> Result<BundleContents> {
+    let mut archive = ZipArchive::new(File::open(bundle_path)?)?;
+
+    let mut manifest_json = String::new();
+    archive.by_name(MANIFEST_ENTRY)
+        .map_err(|_| anyhow!("任务包缺少{}", MANIFEST_ENTRY))?
+        .read_to_string(&mut manifest_json)?;
+    let manifest: TaskBundleManifest = serde_json::from_str(&manifest_json)?;
+
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(anyhow!("不支持的任务包版本: {}", manifest.format_version));
+    }
+
+    let mut history_json = String::new();
+    let history = match archive.by_name(HISTORY_ENTRY) {
+        Ok(mut file) => {
+            file.read_to_string(&mut history_json)?;
+            serde_json::from_str(&history_json)?
+        }
+        Err(_) => Vec::new(),
+    };
+
+    Ok(BundleContents { manifest, history })
+}
+
+fn extract_files(bundle_path: &Path, folder_path: &Path) -> Result<()> {
+    let mut archive = ZipArchive::new(File::open(bundle_path)?)?;
+    std::fs::create_dir_all(folder_path)?;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        // enclosed_name会拒绝绝对路径和"..", 防止解压到任务文件夹之外
+        let Some(name) = file.enclosed_name() else { continue };
+        let Ok(relative) = name.strip_prefix(FILES_PREFIX) else { continue };
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        let target = folder_path.join(relative);
+        if file.is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::io::copy(&mut file, &mut File::create(&target)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+// 在任务根目录下选择一个不冲突的文件夹名
+fn unique_folder_name(task_root: &Path, folder_name: &str) -> String {
+    let mut candidate = folder_name.to_string();
+    let mut suffix = 1;
+    while task_root.join(&candidate).exists() {
+        candidate = format!("{}-{}", folder_name, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+// 从任务包恢复任务，返回新任务ID
+pub async fn import_task_bundle(db: &DatabaseService, bundle_path: &Path, task_root: &Path) -> Result<i64> {
+    let path = bundle_path.to_path_buf();
+    let contents = tokio::task::spawn_blocking(move || read_bundle(&path)).await??;
+    let manifest = contents.manifest;
+
+    let source_name = Path::new(&manifest.task.folder_name)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.'))
+        .ok_or_else(|| anyhow!("任务包中的文件夹名称无效"))?;
+    let folder_name = unique_folder_name(task_root, &source_name);
+    let folder_path = task_root.join(&folder_name);
+
+    // 先登记任务再解压，文件夹监听发现新目录时不会重复创建任务
+    let task_id = db.import_task_folder(
+        &folder_name,
+        &folder_path.to_string_lossy(),
+        &manifest.task.status,
+        manifest.task.created_at,
+    )
+    .await?;
+
+    let bundle = bundle_path.to_path_buf();
+    let target = folder_path.clone();
+    let extracted = tokio::task::spawn_blocking(move || extract_files(&bundle, &target)).await?;
+    if let Err(e) = extracted {
+        let _ = std::fs::remove_dir_all(&folder_path);
+        db.delete_task_folder(task_id).await?;
+        return Err(e);
+    }
+
+    for transition in &manifest.status_transitions {
+        db.save_task_status_transition(
+            task_id,
+            transition.from_status.as_deref(),
+            &transition.to_status,
+            transition.changed_at,
+        )
+        .await?;
+    }
+
+    for history in &contents.history {
+        db.save_optimization_history(&OptimizationHistory {
+            task_id: Some(task_id),
+            ..history.clone()
+        })
+        .await?;
+    }
+
+    Ok(task_id)
+}
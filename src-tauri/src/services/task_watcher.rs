@@ -0,0 +1,252 @@
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc;
+use anyhow::Result;
+
+use super::database::DatabaseService;
+
+pub const TASK_CHANGED_EVENT: &str = "task://changed";
+pub const TASK_PROMPT_FILE: &str = "prompt.md";
+pub const DEFAULT_TASK_ROOT: &str = "CodingPal/tasks";
+
+pub const TASK_STATUS_ACTIVE: &str = "active";
+pub const TASK_STATUS_DONE: &str = "done";
+pub const TASK_STATUS_MISSING: &str = "missing";
+pub const TASK_STATUS_ARCHIVED: &str = "archived";
+
+// 合并连续文件事件的等待时间
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TaskChange {
+    Added { task_id: i64, folder_name: String, folder_path: String },
+    Removed { task_id: i64, folder_path: String },
+    Renamed { task_id: i64, from: String, to: String },
+    PromptEdited { task_id: i64, folder_path: String },
+    StatusChanged { task_id: i64, status: String },
+}
+
+// 一次防抖窗口内收集到的变更
+#[derive(Default)]
+struct PendingChanges {
+    dirty: bool,
+    renames: Vec<(PathBuf, PathBuf)>,
+    prompt_edits: HashSet<PathBuf>,
+}
+
+impl PendingChanges {
+    fn record(&mut self, root: &Path, event: &Event) {
+        if let EventKind::Modify(ModifyKind::Name(RenameMode::Both)) = event.kind {
+            if let [from, to] = event.paths.as_slice() {
+                if is_task_dir(root, from) && is_task_dir(root, to) {
+                    self.renames.push((from.clone(), to.clone()));
+                    self.dirty = true;
+                }
+            }
+            return;
+        }
+
+        for path in &event.paths {
+            if is_task_dir(root, path) {
+                self.dirty = true;
+            } else if let Some(task_dir) = prompt_file_task_dir(root, path) {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    self.prompt_edits.insert(task_dir.to_path_buf());
+                    self.dirty = true;
+                }
+            }
+        }
+    }
+}
+
+fn is_task_dir(root: &Path, path: &Path) -> bool {
+    path.parent() == Some(root)
+}
+
+fn prompt_file_task_dir<'a>(root: &Path, path: &'a Path) -> Option<&'a Path> {
+    if path.file_name()? != TASK_PROMPT_FILE {
+        return None;
+    }
+
+    path.parent().filter(|dir| is_task_dir(root, dir))
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+fn folder_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// 读取设置中的任务根目录，确保其存在并返回绝对路径
+pub async fn resolve_task_root(db: &DatabaseService) -> Result<PathBuf> {
+    let root = db.get_setting("task_root")
+        .await?
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_TASK_ROOT.to_string());
+
+    std::fs::create_dir_all(&root)?;
+    Ok(std::fs::canonicalize(&root)?)
+}
+
+fn list_task_dirs(root: &Path) -> Result<HashSet<PathBuf>> {
+    let mut dirs = HashSet::new();
+    for entry in std::fs::read_dir(root)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            dirs.insert(entry.path());
+        }
+    }
+    Ok(dirs)
+}
+
+// 将磁盘上的任务文件夹与task_folders表对齐，返回产生的变更
+async fn reconcile(
+    db: &DatabaseService,
+    root: &Path,
+    pending: &PendingChanges,
+    disk_dirs: &HashSet<PathBuf>,
+) -> Result<Vec<TaskChange>> {
+    let mut changes = Vec::new();
+
+    // 先处理明确的重命名，避免被当作一删一增
+    for (from, to) in &pending.renames {
+        let from_path = path_string(from);
+        let to_path = path_string(to);
+        if !disk_dirs.contains(to) || db.get_task_folder_by_path(&to_path).await?.is_some() {
+            continue;
+        }
+        if let Some(task) = db.get_task_folder_by_path(&from_path).await? {
+            db.rename_task_folder(task.id, &folder_name(to), &to_path).await?;
+            changes.push(TaskChange::Renamed { task_id: task.id, from: from_path, to: to_path });
+        }
+    }
+
+    let tasks: Vec<_> = db.get_task_folders()
+        .await?
+        .into_iter()
+        .filter(|task| is_task_dir(root, Path::new(&task.folder_path)))
+        .collect();
+    let known: HashSet<PathBuf> = tasks.iter().map(|task| PathBuf::from(&task.folder_path)).collect();
+
+    for task in tasks.iter().filter(|task| task.status != TASK_STATUS_ARCHIVED) {
+        let on_disk = disk_dirs.contains(Path::new(&task.folder_path));
+        if !on_disk && task.status != TASK_STATUS_MISSING {
+            // 文件夹在应用外被删除
+            db.update_task_status(task.id, TASK_STATUS_MISSING).await?;
+            changes.push(TaskChange::Removed { task_id: task.id, folder_path: task.folder_path.clone() });
+        } else if on_disk && task.status == TASK_STATUS_MISSING {
+            db.update_task_status(task.id, TASK_STATUS_ACTIVE).await?;
+            changes.push(TaskChange::Added {
+                task_id: task.id,
+                folder_name: task.folder_name.clone(),
+                folder_path: task.folder_path.clone(),
+            });
+        }
+    }
+
+    for dir in disk_dirs.difference(&known) {
+        let name = folder_name(dir);
+        let path = path_string(dir);
+        let task_id = db.ensure_task_folder(&name, &path).await?;
+        changes.push(TaskChange::Added { task_id, folder_name: name, folder_path: path });
+    }
+
+    for dir in &pending.prompt_edits {
+        let path = path_string(dir);
+        if let Some(task) = db.get_task_folder_by_path(&path).await? {
+            db.touch_task_folder(task.id).await?;
+            changes.push(TaskChange::PromptEdited { task_id: task.id, folder_path: path });
+        }
+    }
+
+    Ok(changes)
+}
+
+// 同步任务文件夹的监听：根目录只看直接子项，每个任务文件夹单独监听其提示词文件
+fn sync_watches(watcher: &mut RecommendedWatcher, watched: &mut HashSet<PathBuf>, disk_dirs: &HashSet<PathBuf>) {
+    watched.retain(|dir| {
+        if disk_dirs.contains(dir) {
+            return true;
+        }
+        let _ = watcher.unwatch(dir);
+        false
+    });
+
+    for dir in disk_dirs {
+        if !watched.contains(dir) && watcher.watch(dir, RecursiveMode::NonRecursive).is_ok() {
+            watched.insert(dir.clone());
+        }
+    }
+}
+
+async fn sync_and_emit(
+    app: &AppHandle,
+    db: &DatabaseService,
+    root: &Path,
+    pending: &PendingChanges,
+    watcher: &mut RecommendedWatcher,
+    watched: &mut HashSet<PathBuf>,
+) {
+    let disk_dirs = match list_task_dirs(root) {
+        Ok(dirs) => dirs,
+        Err(e) => {
+            eprintln!("读取任务目录失败: {}", e);
+            return;
+        }
+    };
+
+    sync_watches(watcher, watched, &disk_dirs);
+
+    match reconcile(db, root, pending, &disk_dirs).await {
+        Ok(changes) => {
+            for change in changes {
+                let _ = app.emit(TASK_CHANGED_EVENT, &change);
+            }
+        }
+        Err(e) => eprintln!("同步任务文件夹失败: {}", e),
+    }
+}
+
+// 启动任务根目录监听，启动时先做一次全量同步
+pub fn start_task_watcher(app: AppHandle, db: DatabaseService, root: PathBuf) -> Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Event>();
+
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            if !event.kind.is_access() {
+                let _ = tx.send(event);
+            }
+        }
+    })?;
+    watcher.watch(&root, RecursiveMode::NonRecursive)?;
+
+    tauri::async_runtime::spawn(async move {
+        let mut watched = HashSet::new();
+        sync_and_emit(&app, &db, &root, &PendingChanges::default(), &mut watcher, &mut watched).await;
+
+        while let Some(event) = rx.recv().await {
+            let mut pending = PendingChanges::default();
+            pending.record(&root, &event);
+
+            while let Ok(Some(event)) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                pending.record(&root, &event);
+            }
+
+            if pending.dirty {
+                sync_and_emit(&app, &db, &root, &pending, &mut watcher, &mut watched).await;
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,329 @@
+// 全局快捷键 - 快捷键保存在数据库中，映射到后端动作，可在运行时重新绑定
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::gesture_actions::{execute_action, GestureAction};
+use crate::gesture_service::toggle_gesture_recognition;
+use crate::tray::{refresh_tray, show_main_window};
+
+pub const SHORTCUT_TRIGGERED_EVENT: &str = "shortcut://triggered";
+
+// 当前已向系统注册的快捷键，重新绑定时先注销旧的
+static REGISTERED_SHORTCUTS: Lazy<Mutex<HashMap<ShortcutAction, Shortcut>>> = Lazy::new(|| {
+    Mutex::new(HashMap::new())
+});
+
+// 可绑定快捷键的动作
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    ShowWindow,
+    OptimizeClipboard,
+    CopyLastOptimized,
+    ToggleGestureRecognition,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 4] = [
+        ShortcutAction::ShowWindow,
+        ShortcutAction::OptimizeClipboard,
+        ShortcutAction::CopyLastOptimized,
+        ShortcutAction::ToggleGestureRecognition,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShortcutAction::ShowWindow => "show_window",
+            ShortcutAction::OptimizeClipboard => "optimize_clipboard",
+            ShortcutAction::CopyLastOptimized => "copy_last_optimized",
+            ShortcutAction::ToggleGestureRecognition => "toggle_gesture_recognition",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.as_str() == value)
+    }
+
+    pub fn default_accelerator(&self) -> &'static str {
+        match self {
+            ShortcutAction::ShowWindow => "CommandOrControl+Alt+C",
+            ShortcutAction::OptimizeClipboard => "CommandOrControl+Alt+O",
+            ShortcutAction::CopyLastOptimized => "CommandOrControl+Alt+V",
+            ShortcutAction::ToggleGestureRecognition => "CommandOrControl+Alt+G",
+        }
+    }
+}
+
+// 快捷键绑定，accelerator为空表示禁用
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShortcutBinding {
+    pub action: ShortcutAction,
+    pub accelerator: Option<String>,
+    pub default_accelerator: String,
+    pub registered: bool, // 是否已成功向系统注册，被其他程序占用时为false
+}
+
+// 初始化快捷键表并写入默认绑定
+pub async fn init_shortcut_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS global_shortcuts (
+            action VARCHAR(50) PRIMARY KEY,
+            accelerator VARCHAR(100),
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    for action in ShortcutAction::ALL {
+        sqlx::query("INSERT OR IGNORE INTO global_shortcuts (action, accelerator) VALUES (?, ?)")
+            .bind(action.as_str())
+            .bind(action.default_accelerator())
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn parse_accelerator(accelerator: &str) -> Result<Shortcut, String> {
+    accelerator
+        .parse::<Shortcut>()
+        .map_err(|e| format!("无效的快捷键 {}: {}", accelerator, e))
+}
+
+async fn load_accelerators(pool: &SqlitePool) -> Result<HashMap<ShortcutAction, Option<String>>, sqlx::Error> {
+    let rows = sqlx::query("SELECT action, accelerator FROM global_shortcuts")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let action: String = row.get("action");
+            ShortcutAction::parse(&action).map(|action| (action, row.get("accelerator")))
+        })
+        .collect())
+}
+
+async fn save_accelerator(pool: &SqlitePool, action: ShortcutAction, accelerator: Option<&str>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO global_shortcuts (action, accelerator, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP)"
+    )
+    .bind(action.as_str())
+    .bind(accelerator)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn run_shortcut_action(app: &AppHandle, action: ShortcutAction) -> Result<(), String> {
+    match action {
+        ShortcutAction::ShowWindow => show_main_window(app).map_err(|e| e.to_string()),
+        ShortcutAction::OptimizeClipboard => execute_action(app, GestureAction::OptimizeClipboard, None).await.map(|_| ()),
+        // 只复制到剪贴板并提示，不模拟按键，由用户在目标位置粘贴
+        ShortcutAction::CopyLastOptimized => {
+            let db = {
+                let db_guard = crate::DATABASE.lock().map_err(|e| e.to_string())?;
+                db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+            };
+            let last = db.get_optimization_history(1)
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .next()
+                .ok_or("还没有优化过的提示词")?;
+            app.clipboard().write_text(last.optimized_prompt).map_err(|e| e.to_string())?;
+            let _ = app.notification()
+                .builder()
+                .title("CodingPal")
+                .body("最近一次优化的提示词已复制到剪贴板")
+                .show();
+            Ok(())
+        }
+        ShortcutAction::ToggleGestureRecognition => {
+            let db = {
+                let db_guard = crate::DATABASE.lock().map_err(|e| e.to_string())?;
+                db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+            };
+            toggle_gesture_recognition(app, &db).await.map(|_| ()).map_err(|e| e.to_string())
+        }
+    }
+}
+
+// 向系统注册快捷键，按下时在后台执行对应动作
+fn register_shortcut(app: &AppHandle, action: ShortcutAction, shortcut: Shortcut) -> Result<(), String> {
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app, _, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = app.emit(SHORTCUT_TRIGGERED_EVENT, action);
+                if let Err(e) = run_shortcut_action(&app, action).await {
+                    eprintln!("执行快捷键动作{}失败: {}", action.as_str(), e);
+                }
+                refresh_tray(&app).await;
+            });
+        })
+        .map_err(|e| format!("快捷键可能已被其他程序占用: {}", e))?;
+
+    REGISTERED_SHORTCUTS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(action, shortcut);
+    Ok(())
+}
+
+fn unregister_shortcut(app: &AppHandle, action: ShortcutAction) -> Result<Option<Shortcut>, String> {
+    let previous = REGISTERED_SHORTCUTS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&action);
+    if let Some(shortcut) = previous {
+        app.global_shortcut().unregister(shortcut).map_err(|e| e.to_string())?;
+    }
+    Ok(previous)
+}
+
+// 启动时注册数据库中的全部快捷键，单个失败不影响其他快捷键
+pub async fn register_global_shortcuts(app: &AppHandle, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    for (action, accelerator) in load_accelerators(pool).await? {
+        let Some(accelerator) = accelerator else { continue };
+        let result = parse_accelerator(&accelerator).and_then(|shortcut| register_shortcut(app, action, shortcut));
+        if let Err(e) = result {
+            eprintln!("注册快捷键{}失败: {}", action.as_str(), e);
+        }
+    }
+    Ok(())
+}
+
+async fn list_bindings(pool: &SqlitePool) -> Result<Vec<ShortcutBinding>, String> {
+    let accelerators = load_accelerators(pool).await.map_err(|e| e.to_string())?;
+    let registered = REGISTERED_SHORTCUTS.lock().map_err(|e| e.to_string())?;
+
+    Ok(ShortcutAction::ALL
+        .into_iter()
+        .map(|action| ShortcutBinding {
+            action,
+            accelerator: accelerators.get(&action).cloned().flatten(),
+            default_accelerator: action.default_accelerator().to_string(),
+            registered: registered.contains_key(&action),
+        })
+        .collect())
+}
+
+// 重新绑定单个动作：检查与其他动作的冲突，注册失败时恢复原快捷键
+async fn rebind(app: &AppHandle, pool: &SqlitePool, action: ShortcutAction, accelerator: Option<String>) -> Result<(), String> {
+    let accelerator = accelerator.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+    let shortcut = accelerator.as_deref().map(parse_accelerator).transpose()?;
+
+    if let Some(shortcut) = shortcut {
+        for (other, other_accelerator) in load_accelerators(pool).await.map_err(|e| e.to_string())? {
+            if other == action {
+                continue;
+            }
+            let conflicts = other_accelerator
+                .as_deref()
+                .and_then(|a| parse_accelerator(a).ok())
+                .is_some_and(|other_shortcut| other_shortcut == shortcut);
+            if conflicts {
+                return Err(format!("快捷键已被{}使用", other.as_str()));
+            }
+        }
+    }
+
+    let previous = unregister_shortcut(app, action)?;
+    if let Some(shortcut) = shortcut {
+        if let Err(e) = register_shortcut(app, action, shortcut) {
+            if let Some(previous) = previous {
+                let _ = register_shortcut(app, action, previous);
+            }
+            return Err(e);
+        }
+    }
+
+    save_accelerator(pool, action, accelerator.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Tauri命令：获取全部快捷键绑定
+#[tauri::command]
+pub async fn get_global_shortcuts() -> Result<Vec<ShortcutBinding>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+
+    list_bindings(db.get_pool()).await
+}
+
+// Tauri命令：修改快捷键，accelerator为空时禁用该动作的快捷键
+#[tauri::command]
+pub async fn set_global_shortcut(
+    app: AppHandle,
+    action: ShortcutAction,
+    accelerator: Option<String>,
+) -> Result<Vec<ShortcutBinding>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+
+    rebind(&app, pool, action, accelerator).await?;
+    list_bindings(pool).await
+}
+
+// Tauri命令：恢复默认快捷键，未指定动作时恢复全部
+#[tauri::command]
+pub async fn reset_global_shortcuts(
+    app: AppHandle,
+    action: Option<ShortcutAction>,
+) -> Result<Vec<ShortcutBinding>, String> {
+    use crate::DATABASE;
+    let db = {
+        let db_guard = DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    let pool = db.get_pool();
+
+    let actions = match action {
+        Some(action) => vec![action],
+        None => {
+            // 先全部注销，避免默认快捷键之间互相判定为冲突
+            for action in ShortcutAction::ALL {
+                unregister_shortcut(&app, action)?;
+                save_accelerator(pool, action, None).await.map_err(|e| e.to_string())?;
+            }
+            ShortcutAction::ALL.to_vec()
+        }
+    };
+
+    let mut errors = Vec::new();
+    for action in actions {
+        if let Err(e) = rebind(&app, pool, action, Some(action.default_accelerator().to_string())).await {
+            errors.push(format!("{}: {}", action.as_str(), e));
+        }
+    }
+
+    if errors.is_empty() {
+        list_bindings(pool).await
+    } else {
+        Err(errors.join("; "))
+    }
+}
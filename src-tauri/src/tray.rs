@@ -0,0 +1,252 @@
+// 系统托盘 - 显示IDE、当前任务与模型状态，并提供常用操作
+use std::time::Duration;
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::gesture_actions::{execute_action, GestureAction, ACTIVE_TASK_CHANGED_EVENT};
+use crate::services::TASK_STATUS_ACTIVE;
+
+pub const TRAY_ID: &str = "main";
+
+// 检查状态变化的间隔
+const REFRESH_INTERVAL: Duration = Duration::from_secs(3);
+
+const MENU_OPTIMIZE_CLIPBOARD: &str = "optimize_clipboard";
+const MENU_TOGGLE_WINDOW: &str = "toggle_window";
+const MENU_TOGGLE_MONITORING: &str = "toggle_monitoring";
+const MENU_QUIT: &str = "quit";
+const MENU_TASK_PREFIX: &str = "task:";
+
+// 托盘展示的状态，变化时才重建菜单
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct TraySnapshot {
+    ides: Vec<String>,
+    tasks: Vec<(i64, String)>,
+    active_task: Option<(i64, String)>,
+    provider_ready: bool,
+    monitoring_paused: bool,
+    window_visible: bool,
+}
+
+impl TraySnapshot {
+    fn ide_label(&self) -> String {
+        if self.monitoring_paused {
+            "IDE监控已暂停".to_string()
+        } else if self.ides.is_empty() {
+            "未检测到运行中的IDE".to_string()
+        } else {
+            format!("IDE: {}", self.ides.join(", "))
+        }
+    }
+
+    fn task_label(&self) -> String {
+        match &self.active_task {
+            Some((_, name)) => format!("当前任务: {}", name),
+            None => "当前任务: 无".to_string(),
+        }
+    }
+
+    fn provider_label(&self) -> &'static str {
+        if self.provider_ready {
+            "GLM: 已连接"
+        } else {
+            "GLM: 未配置"
+        }
+    }
+
+    fn tooltip(&self) -> String {
+        format!("CodingPal\n{}\n{}\n{}", self.ide_label(), self.task_label(), self.provider_label())
+    }
+}
+
+async fn collect_snapshot(app: &AppHandle) -> TraySnapshot {
+    let mut snapshot = TraySnapshot::default();
+
+    // 只读取其他服务采样到的进程，避免托盘额外刷新进程列表
+    if let Ok(monitor) = crate::PROCESS_MONITOR.lock() {
+        snapshot.monitoring_paused = monitor.is_paused();
+        snapshot.ides = monitor.get_tracked_process_names();
+    }
+
+    snapshot.provider_ready = crate::GLM_CLIENT.lock().map(|client| client.is_some()).unwrap_or(false);
+    snapshot.window_visible = app
+        .get_webview_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+
+    let db = crate::DATABASE.lock().ok().and_then(|guard| guard.as_ref().cloned());
+    if let Some(db) = db {
+        let active_id = db.get_active_task_id().await.ok().flatten();
+        if let Ok(tasks) = db.get_task_folders().await {
+            snapshot.active_task = tasks
+                .iter()
+                .find(|task| Some(task.id) == active_id)
+                .map(|task| (task.id, task.folder_name.clone()));
+            snapshot.tasks = tasks
+                .into_iter()
+                .filter(|task| task.status == TASK_STATUS_ACTIVE)
+                .map(|task| (task.id, task.folder_name))
+                .collect();
+        }
+    }
+
+    snapshot
+}
+
+fn build_menu(app: &AppHandle, snapshot: &TraySnapshot) -> tauri::Result<Menu<tauri::Wry>> {
+    let menu = Menu::new(app)?;
+
+    menu.append(&MenuItem::new(app, snapshot.ide_label(), false, None::<&str>)?)?;
+    menu.append(&MenuItem::new(app, snapshot.task_label(), false, None::<&str>)?)?;
+    menu.append(&MenuItem::new(app, snapshot.provider_label(), false, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    menu.append(&MenuItem::with_id(app, MENU_OPTIMIZE_CLIPBOARD, "优化剪贴板", snapshot.provider_ready, None::<&str>)?)?;
+    let window_label = if snapshot.window_visible { "隐藏窗口" } else { "显示窗口" };
+    menu.append(&MenuItem::with_id(app, MENU_TOGGLE_WINDOW, window_label, true, None::<&str>)?)?;
+    menu.append(&CheckMenuItem::with_id(app, MENU_TOGGLE_MONITORING, "暂停监控", true, snapshot.monitoring_paused, None::<&str>)?)?;
+
+    let tasks = Submenu::new(app, "切换任务", !snapshot.tasks.is_empty())?;
+    let active_id = snapshot.active_task.as_ref().map(|(id, _)| *id);
+    for (id, name) in &snapshot.tasks {
+        tasks.append(&CheckMenuItem::with_id(
+            app,
+            format!("{}{}", MENU_TASK_PREFIX, id),
+            name,
+            true,
+            active_id == Some(*id),
+            None::<&str>,
+        )?)?;
+    }
+    menu.append(&tasks)?;
+
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, MENU_QUIT, "退出", true, None::<&str>)?)?;
+
+    Ok(menu)
+}
+
+fn apply_snapshot(app: &AppHandle, snapshot: &TraySnapshot) -> tauri::Result<()> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    tray.set_menu(Some(build_menu(app, snapshot)?))?;
+    tray.set_tooltip(Some(snapshot.tooltip()))
+}
+
+// 立即按当前状态重建托盘菜单
+pub async fn refresh_tray(app: &AppHandle) {
+    let snapshot = collect_snapshot(app).await;
+    if let Err(e) = apply_snapshot(app, &snapshot) {
+        eprintln!("更新托盘菜单失败: {}", e);
+    }
+}
+
+// 显示悬浮窗并置于前台
+pub(crate) fn show_main_window(app: &AppHandle) -> tauri::Result<()> {
+    let Some(window) = app.get_webview_window("main") else {
+        return Ok(());
+    };
+    window.unminimize()?;
+    window.show()?;
+    window.set_focus()
+}
+
+fn toggle_main_window(app: &AppHandle) -> tauri::Result<()> {
+    let Some(window) = app.get_webview_window("main") else {
+        return Ok(());
+    };
+    if window.is_visible()? {
+        window.hide()
+    } else {
+        show_main_window(app)
+    }
+}
+
+async fn activate_task(app: &AppHandle, task_id: i64) -> Result<(), String> {
+    let db = {
+        let db_guard = crate::DATABASE.lock().map_err(|e| e.to_string())?;
+        db_guard.as_ref().ok_or("数据库未初始化")?.clone()
+    };
+    db.set_setting("active_task_id", &task_id.to_string())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(task) = db.get_task_folder(task_id).await.map_err(|e| e.to_string())? {
+        let _ = app.emit(ACTIVE_TASK_CHANGED_EVENT, &task);
+    }
+    Ok(())
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref().to_string();
+    if id == MENU_QUIT {
+        app.exit(0);
+        return;
+    }
+    if id == MENU_TOGGLE_WINDOW {
+        if let Err(e) = toggle_main_window(app) {
+            eprintln!("切换窗口显示失败: {}", e);
+        }
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match id.as_str() {
+            MENU_OPTIMIZE_CLIPBOARD => execute_action(&app, GestureAction::OptimizeClipboard, None).await.map(|_| ()),
+            MENU_TOGGLE_MONITORING => execute_action(&app, GestureAction::ToggleMonitoring, None).await.map(|_| ()),
+            other => match other.strip_prefix(MENU_TASK_PREFIX).and_then(|id| id.parse().ok()) {
+                Some(task_id) => activate_task(&app, task_id).await,
+                None => Ok(()),
+            },
+        };
+        if let Err(e) = result {
+            eprintln!("托盘操作失败: {}", e);
+        }
+        refresh_tray(&app).await;
+    });
+}
+
+// 创建托盘图标并定期检查状态，状态变化时重建菜单
+pub fn init_tray(app: &AppHandle) -> tauri::Result<()> {
+    let snapshot = TraySnapshot::default();
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&build_menu(app, &snapshot)?)
+        .tooltip(snapshot.tooltip())
+        .show_menu_on_left_click(false)
+        .on_menu_event(handle_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            // 左键单击切换悬浮窗显示，右键打开菜单
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                let app = tray.app_handle().clone();
+                if let Err(e) = toggle_main_window(&app) {
+                    eprintln!("切换窗口显示失败: {}", e);
+                }
+                tauri::async_runtime::spawn(async move { refresh_tray(&app).await });
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        let mut last: Option<TraySnapshot> = None;
+        loop {
+            interval.tick().await;
+            let snapshot = collect_snapshot(&app).await;
+            if last.as_ref() == Some(&snapshot) {
+                continue;
+            }
+            if let Err(e) = apply_snapshot(&app, &snapshot) {
+                eprintln!("更新托盘菜单失败: {}", e);
+            }
+            last = Some(snapshot);
+        }
+    });
+
+    Ok(())
+}